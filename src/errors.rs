@@ -27,6 +27,99 @@ pub enum EscrowError {
 
     #[error("Argument provided resulted in overflow")]
     MathError,
+
+    #[error("Take amount provided is zero or exceeds the remaining offer")]
+    InvalidTakeAmount,
+
+    #[error("Offer has expired")]
+    OfferExpired,
+
+    #[error("Offer has not expired yet")]
+    OfferNotExpired,
+
+    #[error("Rent payer key provided does not match expected")]
+    RentPayerKeyMismatch,
+
+    #[error("Token program provided does not match expected")]
+    TokenProgramMismatch,
+
+    #[error("Taker key provided is not allowed to take the offer")]
+    TakerKeyUnauthorized,
+
+    #[error("Withdraw amount provided is not lower than the remaining offer")]
+    InvalidWithdrawAmount,
+
+    #[error("Take amounts exceeded the slippage limits provided")]
+    SlippageExceeded,
+
+    #[error("Account provided is not owned by the program")]
+    AccountOwnerMismatch,
+
+    #[error("Account provided is not of the expected type")]
+    AccountTypeMismatch,
+
+    #[error("Account data provided does not match the expected size")]
+    AccountSizeMismatch,
+
+    #[error("Maker key provided does not match expected")]
+    MakerKeyMismatch,
+
+    #[error("Mint key provided does not match expected")]
+    MintKeyMismatch,
+
+    #[error("Vault balance does not match expected")]
+    VaultBalanceMismatch,
+
+    #[error("Account version does not match expected, it may need to be migrated")]
+    AccountVersionMismatch,
+
+    #[error("Account provided is not in a legacy layout to be migrated")]
+    AccountAlreadyMigrated,
+
+    #[error("Pending manager key provided does not match expected")]
+    PendingManagerKeyMismatch,
+
+    #[error("No pending manager is nominated")]
+    PendingManagerNotSet,
+
+    #[error("Making offers is paused")]
+    MakePaused,
+
+    #[error("Taking offers is paused")]
+    TakePaused,
+
+    #[error("Fee override key provided does not match expected")]
+    FeeOverrideKeyMismatch,
+
+    #[error("Effective time provided is earlier than the fee change delay allows")]
+    FeeChangeTooEarly,
+
+    #[error("No pending fees are announced")]
+    PendingFeesNotSet,
+
+    #[error("Key provided is neither the manager nor the fee collector")]
+    FeeCollectorKeyUnauthorized,
+
+    #[error("Fee exemption key provided does not match expected")]
+    FeeExemptionKeyMismatch,
+
+    #[error("Fee exemption provided does not apply to the wallet")]
+    FeeExemptionWalletMismatch,
+
+    #[error("Lamport fee vault key provided does not match expected")]
+    LamportFeeVaultKeyMismatch,
+
+    #[error("Destination provided is not the fee destination")]
+    FeeDestinationKeyMismatch,
+
+    #[error("Mint with a transfer hook is not supported, use `CollectFee` instead")]
+    TransferHookNotSupported,
+
+    #[error("Referrer key provided does not match the referrer in the instruction data")]
+    ReferrerKeyMismatch,
+
+    #[error("Referrer provided is the taker or the maker of the offer")]
+    ReferrerKeyUnauthorized,
 }
 
 impl From<EscrowError> for ProgramError {
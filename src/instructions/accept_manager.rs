@@ -0,0 +1,52 @@
+//! Instruction for the nominated manager to accept the authority.
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    errors::EscrowError,
+    state::EscrowState,
+    utils::{load_program_account, save_program_account},
+};
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        new_manager,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the new manager signs the instruction
+    if !new_manager.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize the escrow state and create program address
+    let mut escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure the provided escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided new manager is the nominated one
+    match escrow_state.pending_manager {
+        Some(pending_manager) if pending_manager == *new_manager.key => {}
+        Some(_) => return Err(EscrowError::PendingManagerKeyMismatch.into()),
+        None => return Err(EscrowError::PendingManagerNotSet.into()),
+    }
+
+    escrow_state.manager = *new_manager.key;
+    escrow_state.pending_manager = None;
+
+    // Write data into escrow state account
+    save_program_account(escrow_state_info, &escrow_state)?;
+
+    solana_program::msg!("Accepted manager in the escrow state: {:?}", escrow_state);
+
+    Ok(())
+}
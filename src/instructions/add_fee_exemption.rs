@@ -0,0 +1,96 @@
+//! Instruction for manager to discount the fees of a wallet (e.g. market makers), as maker
+//! or taker.
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+use crate::{
+    errors::EscrowError,
+    state::{EscrowState, FeeExemption, ProgramAccount},
+    utils::{
+        assert_is_bps_in_range, create_program_account, load_program_account, save_program_account,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct AddFeeExemptionArgs {
+    /// Wallet whose fees are discounted
+    pub wallet: Pubkey,
+    /// Discount of the fee rates, in basis points of the rates (full exemption at 10_000)
+    pub discount_bps: u16,
+    /// Optional unix timestamp from which the exemption no longer applies
+    pub expires_at: Option<i64>,
+}
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: AddFeeExemptionArgs,
+) -> ProgramResult {
+    // Check the range of bps values in args
+    assert_is_bps_in_range(args.discount_bps)?;
+
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        fee_exemption_info,
+        manager,
+        payer,
+        system_program,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the manager signs the instruction
+    if !manager.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize the escrow state and create program address
+    let escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure the provided escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided manager is authorized
+    if *manager.key != escrow_state.manager {
+        return Err(EscrowError::ManagerKeyUnauthorized.into());
+    }
+
+    // Prepare a new fee exemption and its address
+    let (fee_exemption, fee_exemption_address) =
+        FeeExemption::new(program_id, args.wallet, args.discount_bps, args.expires_at);
+
+    // Ensure the provided fee exemption address is correct
+    if *fee_exemption_info.key != fee_exemption_address {
+        return Err(EscrowError::FeeExemptionKeyMismatch.into());
+    };
+
+    // Create fee exemption account (it may be funded beforehand)
+    create_program_account(
+        program_id,
+        fee_exemption_info,
+        payer,
+        system_program,
+        FeeExemption::LEN,
+        &[
+            FeeExemption::SEED_PREFIX,
+            fee_exemption.wallet.as_ref(),
+            &[fee_exemption.bump],
+        ],
+    )?;
+
+    // Write data into fee exemption account
+    save_program_account(fee_exemption_info, &fee_exemption)?;
+
+    solana_program::msg!("Added fee exemption: {:?}", fee_exemption);
+
+    Ok(())
+}
@@ -6,14 +6,21 @@ use {
         entrypoint::ProgramResult,
         program::{invoke, invoke_signed},
         program_error::ProgramError,
-        program_pack::Pack,
         pubkey::Pubkey,
     },
     spl_associated_token_account::instruction as associated_token_account_instruction,
-    spl_token::{instruction as token_instruction, state::Account as TokenAccount},
+    spl_token_2022::{instruction as token_instruction, onchain::invoke_transfer_checked},
 };
 
-use crate::{errors::EscrowError, state::Offer, utils::assert_is_associated_token_account};
+use crate::{
+    errors::EscrowError,
+    state::Offer,
+    utils::{
+        assert_is_associated_token_account, assert_is_token_program_of_mint, get_mint_decimals,
+        get_transfer_fee, harvest_withheld_transfer_fees, is_native_mint, load_program_account,
+        unpack_token_account, unwrap_native,
+    },
+};
 
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
 pub struct CancelOfferArgs {}
@@ -26,10 +33,12 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         maker_token_a_account,
         vault,
         maker,
+        rent_payer,
         payer,
         token_program,
         associated_token_program,
         system_program,
+        remaining_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
@@ -39,12 +48,19 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
+    // Validate the token program of the offered token
+    assert_is_token_program_of_mint(token_program, token_a_mint)?;
+
     // Deserialize the offer
-    let offer = Offer::try_from_slice(&offer_info.data.borrow()[..])?;
+    let offer = load_program_account::<Offer>(program_id, offer_info)?;
 
     // Validate the offer
-    assert_eq!(&offer.maker, maker.key);
-    assert_eq!(&offer.token_a_mint, token_a_mint.key);
+    if offer.maker != *maker.key {
+        return Err(EscrowError::MakerKeyMismatch.into());
+    }
+    if offer.token_a_mint != *token_a_mint.key {
+        return Err(EscrowError::MintKeyMismatch.into());
+    }
 
     // Create program address of the offer
     let offer_address = Offer::create_program_address(program_id, maker.key, offer.id, offer.bump)?;
@@ -54,6 +70,11 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         return Err(EscrowError::OfferKeyMismatch.into());
     };
 
+    // Ensure the rent is sent back to the original funder of the offer
+    if *rent_payer.key != offer.rent_payer {
+        return Err(EscrowError::RentPayerKeyMismatch.into());
+    }
+
     let offer_signer_seed = &[
         Offer::SEED_PREFIX,
         maker.key.as_ref(),
@@ -62,95 +83,115 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     ];
 
     // Validate the receiving token A accout is owned by the maker (ATA)
-    assert_is_associated_token_account(maker_token_a_account.key, maker.key, token_a_mint.key)?;
+    assert_is_associated_token_account(
+        maker_token_a_account.key,
+        maker.key,
+        token_a_mint.key,
+        token_program.key,
+    )?;
 
     // Validate vault is owned by the offer account (ATA)
-    assert_is_associated_token_account(vault.key, offer_info.key, token_a_mint.key)?;
-
-    // Create maker token A account (ATA) if needed, before receiveing tokens
-    invoke(
-        &associated_token_account_instruction::create_associated_token_account_idempotent(
-            payer.key,
-            maker.key,
-            token_a_mint.key,
-            token_program.key,
-        ),
-        //   0. `[writeable,signer]` Funding account (must be a system account)
-        //   1. `[writeable]` Associated token account address to be created
-        //   2. `[]` Wallet address for the new associated token account
-        //   3. `[]` The token mint for the new associated token account
-        //   4. `[]` System program
-        //   5. `[]` SPL Token program
-        &[
-            payer.clone(),
-            maker_token_a_account.clone(),
-            maker.clone(),
-            token_a_mint.clone(),
-            system_program.clone(),
-            token_program.clone(),
-            associated_token_program.clone(),
-        ],
+    assert_is_associated_token_account(
+        vault.key,
+        offer_info.key,
+        token_a_mint.key,
+        token_program.key,
     )?;
 
     // Read token amount in the offer's vault account
-    let vault_amount = TokenAccount::unpack(&vault.data.borrow())?.amount;
-    let maker_amount_before_transfer =
-        TokenAccount::unpack(&maker_token_a_account.data.borrow())?.amount;
-
-    // Transfer (refund) token A in vault to maker
-    invoke_signed(
-        &token_instruction::transfer(
+    let vault_amount = unpack_token_account(vault)?.amount;
+
+    // NOTE: Native SOL is refunded to the maker's wallet, leaving any token account of the maker
+    // untouched
+    if is_native_mint(token_a_mint.key) {
+        // Unwrap SOL in vault into the offer account, closing the vault
+        unwrap_native(token_program, vault, offer_info, &[offer_signer_seed])?;
+
+        // Refund SOL to maker, the rest of the vault lamports being rent
+        **offer_info.lamports.borrow_mut() -= vault_amount;
+        **maker.lamports.borrow_mut() += vault_amount;
+    } else {
+        // Create maker token A account (ATA) if needed, before receiveing tokens
+        invoke(
+            &associated_token_account_instruction::create_associated_token_account_idempotent(
+                payer.key,
+                maker.key,
+                token_a_mint.key,
+                token_program.key,
+            ),
+            //   0. `[writeable,signer]` Funding account (must be a system account)
+            //   1. `[writeable]` Associated token account address to be created
+            //   2. `[]` Wallet address for the new associated token account
+            //   3. `[]` The token mint for the new associated token account
+            //   4. `[]` System program
+            //   5. `[]` SPL Token program
+            &[
+                payer.clone(),
+                maker_token_a_account.clone(),
+                maker.clone(),
+                token_a_mint.clone(),
+                system_program.clone(),
+                token_program.clone(),
+                associated_token_program.clone(),
+            ],
+        )?;
+
+        let maker_amount_before_transfer = unpack_token_account(maker_token_a_account)?.amount;
+
+        // Transfer (refund) token A in vault to maker
+        let token_a_decimals = get_mint_decimals(token_a_mint)?;
+        invoke_transfer_checked(
             token_program.key,
-            vault.key,
-            maker_token_a_account.key,
-            offer_info.key,
-            &[],
-            vault_amount,
-        )?,
-        //   0. `[writable]` The source account.
-        //   1. `[writable]` The destination account.
-        //   2. `[signer]` The source account's owner/delegate.
-        &[
             vault.clone(),
+            token_a_mint.clone(),
             maker_token_a_account.clone(),
             offer_info.clone(),
-            token_program.clone(),
-        ],
-        &[offer_signer_seed],
-    )?;
-
-    let maker_amount_after_transfer =
-        TokenAccount::unpack(&maker_token_a_account.data.borrow())?.amount;
-    assert_eq!(
-        maker_amount_after_transfer,
-        maker_amount_before_transfer + vault_amount
-    );
-
-    // Close the vault account
-    invoke_signed(
-        &token_instruction::close_account(
-            token_program.key,
-            vault.key,
-            payer.key,
-            offer_info.key,
-            &[],
-        )?,
-        //   0. `[writable]` The account to close.
-        //   1. `[writable]` The destination account.
-        //   2. `[signer]` The account's owner.
-        &[
-            vault.clone(),
-            payer.clone(),
-            offer_info.clone(),
-            token_program.clone(),
-        ],
-        &[offer_signer_seed],
-    )?;
+            remaining_accounts,
+            vault_amount,
+            token_a_decimals,
+            &[offer_signer_seed],
+        )?;
+
+        // Ensure the whole vault was refunded to the maker (net of any Token-2022 transfer fee)
+        let maker_amount_after_transfer = unpack_token_account(maker_token_a_account)?.amount;
+        let token_a_transfer_fee = get_transfer_fee(token_a_mint, vault_amount)?;
+        let maker_amount_expected = maker_amount_before_transfer
+            .checked_add(vault_amount)
+            .and_then(|v| v.checked_sub(token_a_transfer_fee))
+            .ok_or(EscrowError::MathError)?;
+        if maker_amount_after_transfer != maker_amount_expected {
+            return Err(EscrowError::VaultBalanceMismatch.into());
+        }
+
+        // Harvest withheld Token-2022 transfer fees (if any), before closing the vault
+        harvest_withheld_transfer_fees(token_program, token_a_mint, vault)?;
+
+        // Close the vault account
+        invoke_signed(
+            &token_instruction::close_account(
+                token_program.key,
+                vault.key,
+                rent_payer.key,
+                offer_info.key,
+                &[],
+            )?,
+            //   0. `[writable]` The account to close.
+            //   1. `[writable]` The destination account.
+            //   2. `[signer]` The account's owner.
+            &[
+                vault.clone(),
+                rent_payer.clone(),
+                offer_info.clone(),
+                token_program.clone(),
+            ],
+            &[offer_signer_seed],
+        )?;
+    }
 
-    // Send the rent back to the payer
+    // Send the rent back to the original funder
     let lamports = offer_info.lamports();
     **offer_info.lamports.borrow_mut() -= lamports;
-    **payer.lamports.borrow_mut() += lamports;
+    **rent_payer.lamports.borrow_mut() += lamports;
 
     // Realloc the account to zero
     offer_info.realloc(0, true)?;
@@ -0,0 +1,58 @@
+//! Instruction for manager to retract the escrow fees announced with `SetFees`.
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+    program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+};
+
+use crate::{
+    errors::EscrowError,
+    state::EscrowState,
+    utils::{load_program_account, save_program_account},
+};
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        manager,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the manager signs the instruction
+    if !manager.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize the escrow state and create program address
+    let mut escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure the provided escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided manager is authorized
+    if *manager.key != escrow_state.manager {
+        return Err(EscrowError::ManagerKeyUnauthorized.into());
+    }
+
+    // Ensure fees are pending, those already in effect can no longer be retracted
+    escrow_state.apply_pending_fees(Clock::get()?.unix_timestamp);
+    if escrow_state.pending_fees.is_none() {
+        return Err(EscrowError::PendingFeesNotSet.into());
+    }
+
+    escrow_state.pending_fees = None;
+
+    // Write data into escrow state account
+    save_program_account(escrow_state_info, &escrow_state)?;
+
+    solana_program::msg!(
+        "Canceled pending fees in the escrow state: {:?}",
+        escrow_state
+    );
+
+    Ok(())
+}
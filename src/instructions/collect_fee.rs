@@ -1,14 +1,21 @@
-//! Instruction for manager to collect accumulated fee from an escrow fee account.
+//! Instruction for manager or fee collector to collect accumulated fee from an escrow fee account.
 use {
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
         account_info::AccountInfo, entrypoint::ProgramResult, program::invoke_signed,
-        program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+        program_error::ProgramError, pubkey::Pubkey,
     },
-    spl_token::{instruction as token_instruction, state::Account as TokenAccount},
+    spl_token_2022::{instruction as token_instruction, onchain::invoke_transfer_checked},
 };
 
-use crate::{errors::EscrowError, state::EscrowState, utils::assert_is_associated_token_account};
+use crate::{
+    errors::EscrowError,
+    state::EscrowState,
+    utils::{
+        assert_is_associated_token_account, assert_is_token_program_of_mint, get_mint_decimals,
+        harvest_withheld_transfer_fees, load_program_account, unpack_token_account,
+    },
+};
 
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
 pub struct CollectFeeArgs {
@@ -23,22 +30,23 @@ pub fn process(
     let [
         // accounts in order (see `crate::instruction::EscrowInstruction` enum)
         escrow_state_info,
-        manager,
+        authority,
         escrow_fee_mint,
         escrow_fee_account,
         destination_token_account,
         token_program,
+        remaining_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    // Ensure the manager signs the instruction
-    if !manager.is_signer {
+    // Ensure the manager or fee collector signs the instruction
+    if !authority.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
     // Deserialize the escrow state and create program address
-    let escrow_state = EscrowState::try_from_slice(&escrow_state_info.data.borrow()[..])?;
+    let escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
     let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
 
     // Ensure escrow state address is correct
@@ -46,51 +54,94 @@ pub fn process(
         return Err(EscrowError::EscrowStateKeyMismatch.into());
     };
 
-    // Ensure the provided manager is authorized
-    if *manager.key != escrow_state.manager {
-        return Err(EscrowError::ManagerKeyUnauthorized.into());
+    // Ensure the provided authority is the manager or the fee collector
+    if *authority.key != escrow_state.manager && Some(*authority.key) != escrow_state.fee_collector
+    {
+        return Err(EscrowError::FeeCollectorKeyUnauthorized.into());
+    }
+
+    let fee_amount = sweep_fee_account(
+        &escrow_state,
+        escrow_state_info,
+        authority,
+        escrow_fee_mint,
+        escrow_fee_account,
+        destination_token_account,
+        token_program,
+        remaining_accounts,
+        args.should_close_fee_account,
+    )?;
+
+    if fee_amount != 0 {
+        solana_program::msg!("Collected fee: {}", fee_amount);
     }
 
+    Ok(())
+}
+
+/// Transfers the whole balance of an escrow fee account to the destination token account, and
+/// closes the fee account if requested (sending its rent to the authority). The transfer hook
+/// accounts are the extra accounts required by the transfer hook of the mint (Token-2022), if any.
+///
+/// Returns the collected amount. The authority is expected to be checked by the caller.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn sweep_fee_account<'a>(
+    escrow_state: &EscrowState,
+    escrow_state_info: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    escrow_fee_mint: &AccountInfo<'a>,
+    escrow_fee_account: &AccountInfo<'a>,
+    destination_token_account: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    transfer_hook_accounts: &[AccountInfo<'a>],
+    should_close_fee_account: bool,
+) -> Result<u64, ProgramError> {
+    // Validate the token program of the escrow fee token
+    assert_is_token_program_of_mint(token_program, escrow_fee_mint)?;
+
     // Validate the escrow fee account
     assert_is_associated_token_account(
         escrow_fee_account.key,
         escrow_state_info.key,
         escrow_fee_mint.key,
+        token_program.key,
     )?;
 
-    let fee_amount = TokenAccount::unpack(&escrow_fee_account.data.borrow())?.amount;
+    // Ensure fees are collected to the fee destination (ATA), if pinned
+    if let Some(fee_destination) = escrow_state.fee_destination {
+        assert_is_associated_token_account(
+            destination_token_account.key,
+            &fee_destination,
+            escrow_fee_mint.key,
+            token_program.key,
+        )?;
+    }
+
+    let fee_amount = unpack_token_account(escrow_fee_account)?.amount;
 
     let escrow_state_signer_seed = &[EscrowState::SEED, &[escrow_state.bump]];
     if fee_amount != 0 {
-        invoke_signed(
-            &token_instruction::transfer(
-                token_program.key,
-                escrow_fee_account.key,
-                destination_token_account.key,
-                escrow_state_info.key,
-                &[],
-                fee_amount,
-            )?,
-            // 0. `[writable]` The source account.
-            // 1. `[writable]` The destination account.
-            // 2. `[signer]` The source account's owner/delegate.
-            &[
-                escrow_fee_account.clone(),
-                destination_token_account.clone(),
-                escrow_state_info.clone(),
-                token_program.clone(),
-            ],
+        invoke_transfer_checked(
+            token_program.key,
+            escrow_fee_account.clone(),
+            escrow_fee_mint.clone(),
+            destination_token_account.clone(),
+            escrow_state_info.clone(),
+            transfer_hook_accounts,
+            fee_amount,
+            get_mint_decimals(escrow_fee_mint)?,
             &[escrow_state_signer_seed],
         )?;
-
-        solana_program::msg!("Collected fee: {}", fee_amount);
     }
-    if args.should_close_fee_account {
+    if should_close_fee_account {
+        // Harvest withheld Token-2022 transfer fees (if any), before closing the fee account
+        harvest_withheld_transfer_fees(token_program, escrow_fee_mint, escrow_fee_account)?;
+
         invoke_signed(
             &token_instruction::close_account(
                 token_program.key,
                 escrow_fee_account.key,
-                manager.key,
+                authority.key,
                 escrow_state_info.key,
                 &[],
             )?,
@@ -99,7 +150,7 @@ pub fn process(
             // 2. `[signer]` The account's owner.
             &[
                 escrow_fee_account.clone(),
-                manager.clone(),
+                authority.clone(),
                 escrow_state_info.clone(),
                 token_program.clone(),
             ],
@@ -107,5 +158,5 @@ pub fn process(
         )?;
     }
 
-    Ok(())
+    Ok(fee_amount)
 }
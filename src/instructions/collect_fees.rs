@@ -0,0 +1,101 @@
+//! Instruction for manager or fee collector to collect accumulated fees from many escrow fee
+//! accounts at once.
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+use crate::{
+    errors::EscrowError,
+    instructions::collect_fee::sweep_fee_account,
+    state::EscrowState,
+    utils::{has_transfer_hook, load_program_account},
+};
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug)]
+pub struct CollectFeesArgs {
+    pub should_close_fee_accounts: bool,
+}
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CollectFeesArgs,
+) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        authority,
+        token_program,
+        token_2022_program,
+        remaining_accounts @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the manager or fee collector signs the instruction
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize the escrow state and create program address
+    let escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided authority is the manager or the fee collector
+    if *authority.key != escrow_state.manager && Some(*authority.key) != escrow_state.fee_collector
+    {
+        return Err(EscrowError::FeeCollectorKeyUnauthorized.into());
+    }
+
+    // Ensure the token programs are SPL Token and Token-2022, respectively
+    if *token_program.key != spl_token::ID || *token_2022_program.key != spl_token_2022::ID {
+        return Err(EscrowError::TokenProgramMismatch.into());
+    }
+
+    // Ensure the remaining accounts are complete (mint, fee account, destination) triples
+    if remaining_accounts.len() % 3 != 0 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    for fee_accounts in remaining_accounts.chunks_exact(3) {
+        let (escrow_fee_mint, escrow_fee_account, destination_token_account) =
+            (&fee_accounts[0], &fee_accounts[1], &fee_accounts[2]);
+
+        // Use the token program owning the mint (validated when sweeping)
+        let mint_token_program = if *escrow_fee_mint.owner == spl_token_2022::ID {
+            token_2022_program
+        } else {
+            token_program
+        };
+
+        // Ensure the mint has no transfer hook, whose extra accounts are not passed per mint
+        if *mint_token_program.key == spl_token_2022::ID && has_transfer_hook(escrow_fee_mint)? {
+            return Err(EscrowError::TransferHookNotSupported.into());
+        }
+
+        let fee_amount = sweep_fee_account(
+            &escrow_state,
+            escrow_state_info,
+            authority,
+            escrow_fee_mint,
+            escrow_fee_account,
+            destination_token_account,
+            mint_token_program,
+            &[],
+            args.should_close_fee_accounts,
+        )?;
+
+        solana_program::msg!("Collected fee of {}: {}", escrow_fee_mint.key, fee_amount);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! Instruction for manager or fee collector to collect the flat lamport fees accumulated in the
+//! lamport fee vault.
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey, rent::Rent, sysvar::Sysvar,
+};
+
+use crate::{
+    errors::EscrowError,
+    state::{EscrowState, LamportFeeVault},
+    utils::load_program_account,
+};
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        authority,
+        lamport_fee_vault_info,
+        destination,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the manager or fee collector signs the instruction
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize the escrow state and create program address
+    let escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided authority is the manager or the fee collector
+    if *authority.key != escrow_state.manager && Some(*authority.key) != escrow_state.fee_collector
+    {
+        return Err(EscrowError::FeeCollectorKeyUnauthorized.into());
+    }
+
+    // Deserialize the lamport fee vault
+    let lamport_fee_vault =
+        load_program_account::<LamportFeeVault>(program_id, lamport_fee_vault_info)?;
+
+    // Ensure the provided lamport fee vault address is correct
+    if *lamport_fee_vault_info.key
+        != LamportFeeVault::create_program_address(program_id, lamport_fee_vault.bump)?
+    {
+        return Err(EscrowError::LamportFeeVaultKeyMismatch.into());
+    }
+
+    // Ensure fees are collected to the fee destination, if pinned
+    if escrow_state
+        .fee_destination
+        .is_some_and(|fee_destination| fee_destination != *destination.key)
+    {
+        return Err(EscrowError::FeeDestinationKeyMismatch.into());
+    }
+
+    // Send the lamports above the rent of the vault to the destination
+    let fee_amount = lamport_fee_vault_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(lamport_fee_vault_info.data_len()));
+    if fee_amount != 0 {
+        **lamport_fee_vault_info.lamports.borrow_mut() -= fee_amount;
+        **destination.lamports.borrow_mut() += fee_amount;
+
+        solana_program::msg!("Collected lamport fees: {}", fee_amount);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! Instruction for manager to create fee rates overriding the programwide fees for a mint pair,
+//! or for any offer of a single mint.
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+use crate::{
+    errors::EscrowError,
+    state::{EscrowState, FeeOverride, ProgramAccount},
+    utils::{
+        assert_is_bps_in_range, create_program_account, load_program_account, save_program_account,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct CreateFeeOverrideArgs {
+    /// Token A mint of the offers, or either mint of the offers if `paired_mint` is not set
+    pub mint: Pubkey,
+    /// Token B mint of the offers, to override the fees of a mint pair
+    pub paired_mint: Option<Pubkey>,
+    pub maker_fee_bps: u16,
+    pub taker_fee_bps: u16,
+}
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: CreateFeeOverrideArgs,
+) -> ProgramResult {
+    // Check the range of bps values in args
+    assert_is_bps_in_range(args.maker_fee_bps)?;
+    assert_is_bps_in_range(args.taker_fee_bps)?;
+
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        fee_override_info,
+        manager,
+        payer,
+        system_program,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the manager signs the instruction
+    if !manager.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize the escrow state and create program address
+    let escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure the provided escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided manager is authorized
+    if *manager.key != escrow_state.manager {
+        return Err(EscrowError::ManagerKeyUnauthorized.into());
+    }
+
+    // Prepare a new fee override and its address
+    let (fee_override, fee_override_address) = FeeOverride::new(
+        program_id,
+        args.mint,
+        args.paired_mint,
+        args.maker_fee_bps,
+        args.taker_fee_bps,
+    );
+
+    // Ensure the provided fee override address is correct
+    if *fee_override_info.key != fee_override_address {
+        return Err(EscrowError::FeeOverrideKeyMismatch.into());
+    };
+
+    // Create fee override account (it may be funded beforehand)
+    let fee_override_signer_seed: &[&[u8]] = match &fee_override.paired_mint {
+        Some(paired_mint) => &[
+            FeeOverride::SEED_PREFIX,
+            fee_override.mint.as_ref(),
+            paired_mint.as_ref(),
+            &[fee_override.bump],
+        ],
+        None => &[
+            FeeOverride::SEED_PREFIX,
+            fee_override.mint.as_ref(),
+            &[fee_override.bump],
+        ],
+    };
+    create_program_account(
+        program_id,
+        fee_override_info,
+        payer,
+        system_program,
+        FeeOverride::LEN,
+        fee_override_signer_seed,
+    )?;
+
+    // Write data into fee override account
+    save_program_account(fee_override_info, &fee_override)?;
+
+    solana_program::msg!("Created fee override: {:?}", fee_override);
+
+    Ok(())
+}
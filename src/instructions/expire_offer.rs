@@ -0,0 +1,187 @@
+//! Permissionless instruction to close an expired offer.
+use {
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Clock,
+        entrypoint::ProgramResult,
+        program::{invoke, invoke_signed},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+    spl_associated_token_account::instruction as associated_token_account_instruction,
+    spl_token_2022::{instruction as token_instruction, onchain::invoke_transfer_checked},
+};
+
+use crate::{
+    errors::EscrowError,
+    state::Offer,
+    utils::{
+        assert_is_associated_token_account, assert_is_token_program_of_mint, get_mint_decimals,
+        harvest_withheld_transfer_fees, is_native_mint, load_program_account, unpack_token_account,
+        unwrap_native,
+    },
+};
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        offer_info,
+        token_a_mint,
+        maker_token_a_account,
+        vault,
+        maker,
+        rent_payer,
+        payer,
+        token_program,
+        associated_token_program,
+        system_program,
+        remaining_accounts @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Validate the token program of the offered token
+    assert_is_token_program_of_mint(token_program, token_a_mint)?;
+
+    // Deserialize the offer
+    let offer = load_program_account::<Offer>(program_id, offer_info)?;
+
+    // Validate the offer
+    if offer.maker != *maker.key {
+        return Err(EscrowError::MakerKeyMismatch.into());
+    }
+    if offer.token_a_mint != *token_a_mint.key {
+        return Err(EscrowError::MintKeyMismatch.into());
+    }
+
+    // Create program address of the offer
+    let offer_address = Offer::create_program_address(program_id, maker.key, offer.id, offer.bump)?;
+
+    // Ensure the provided offer address is correct
+    if *offer_info.key != offer_address {
+        return Err(EscrowError::OfferKeyMismatch.into());
+    };
+
+    // Ensure the offer has expired
+    if !offer.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(EscrowError::OfferNotExpired.into());
+    }
+
+    // Ensure the rent is sent back to the original funder of the offer
+    if *rent_payer.key != offer.rent_payer {
+        return Err(EscrowError::RentPayerKeyMismatch.into());
+    }
+
+    let offer_signer_seed = &[
+        Offer::SEED_PREFIX,
+        maker.key.as_ref(),
+        &offer.id.to_le_bytes(),
+        &[offer.bump],
+    ];
+
+    // Validate the receiving token A accout is owned by the maker (ATA)
+    assert_is_associated_token_account(
+        maker_token_a_account.key,
+        maker.key,
+        token_a_mint.key,
+        token_program.key,
+    )?;
+
+    // Validate vault is owned by the offer account (ATA)
+    assert_is_associated_token_account(
+        vault.key,
+        offer_info.key,
+        token_a_mint.key,
+        token_program.key,
+    )?;
+
+    // Read token amount in the offer's vault account
+    let vault_amount = unpack_token_account(vault)?.amount;
+
+    // NOTE: Native SOL is refunded to the maker's wallet, as the maker does not sign to unwrap it
+    if is_native_mint(token_a_mint.key) {
+        // Unwrap SOL in vault into the offer account, closing the vault
+        unwrap_native(token_program, vault, offer_info, &[offer_signer_seed])?;
+
+        // Refund SOL to maker, the rest of the vault lamports being rent
+        **offer_info.lamports.borrow_mut() -= vault_amount;
+        **maker.lamports.borrow_mut() += vault_amount;
+    } else {
+        // Create maker token A account (ATA) if needed, before receiveing tokens
+        invoke(
+            &associated_token_account_instruction::create_associated_token_account_idempotent(
+                payer.key,
+                maker.key,
+                token_a_mint.key,
+                token_program.key,
+            ),
+            //   0. `[writeable,signer]` Funding account (must be a system account)
+            //   1. `[writeable]` Associated token account address to be created
+            //   2. `[]` Wallet address for the new associated token account
+            //   3. `[]` The token mint for the new associated token account
+            //   4. `[]` System program
+            //   5. `[]` SPL Token program
+            &[
+                payer.clone(),
+                maker_token_a_account.clone(),
+                maker.clone(),
+                token_a_mint.clone(),
+                system_program.clone(),
+                token_program.clone(),
+                associated_token_program.clone(),
+            ],
+        )?;
+
+        // Transfer (refund) token A in vault to maker
+        let token_a_decimals = get_mint_decimals(token_a_mint)?;
+        invoke_transfer_checked(
+            token_program.key,
+            vault.clone(),
+            token_a_mint.clone(),
+            maker_token_a_account.clone(),
+            offer_info.clone(),
+            remaining_accounts,
+            vault_amount,
+            token_a_decimals,
+            &[offer_signer_seed],
+        )?;
+
+        // Harvest withheld Token-2022 transfer fees (if any), before closing the vault
+        harvest_withheld_transfer_fees(token_program, token_a_mint, vault)?;
+
+        // Close the vault account
+        invoke_signed(
+            &token_instruction::close_account(
+                token_program.key,
+                vault.key,
+                rent_payer.key,
+                offer_info.key,
+                &[],
+            )?,
+            //   0. `[writable]` The account to close.
+            //   1. `[writable]` The destination account.
+            //   2. `[signer]` The account's owner.
+            &[
+                vault.clone(),
+                rent_payer.clone(),
+                offer_info.clone(),
+                token_program.clone(),
+            ],
+            &[offer_signer_seed],
+        )?;
+    }
+
+    // Send the rent back to the original funder
+    let lamports = offer_info.lamports();
+    **offer_info.lamports.borrow_mut() -= lamports;
+    **rent_payer.lamports.borrow_mut() += lamports;
+
+    // Realloc the account to zero
+    offer_info.realloc(0, true)?;
+
+    // Assign the account to the System Program
+    offer_info.assign(system_program.key);
+
+    Ok(())
+}
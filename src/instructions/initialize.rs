@@ -9,7 +9,10 @@ use {
 };
 
 use crate::{
-    consts::INITIAL_MANAGER, errors::EscrowError, state::EscrowState, utils::assert_is_bps_in_range,
+    consts::INITIAL_MANAGER,
+    errors::EscrowError,
+    state::{EscrowState, ProgramAccount},
+    utils::{assert_is_bps_in_range, save_program_account},
 };
 
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
@@ -61,8 +64,7 @@ pub fn process(
     };
 
     // Create escrow state account
-    let size = borsh::to_vec::<EscrowState>(&escrow_state)?.len();
-    let lamports_required = Rent::get()?.minimum_balance(size);
+    let lamports_required = Rent::get()?.minimum_balance(EscrowState::LEN);
 
     let escrow_state_signer_seed = &[EscrowState::SEED, &[escrow_state.bump]];
     invoke_signed(
@@ -70,7 +72,7 @@ pub fn process(
             payer.key,
             escrow_state_info.key,
             lamports_required,
-            size as u64,
+            EscrowState::LEN as u64,
             program_id,
         ),
         //   0. `[WRITE, SIGNER]` Funding account
@@ -84,7 +86,7 @@ pub fn process(
     )?;
 
     // Write data into escrow state account
-    escrow_state.serialize(&mut *escrow_state_info.data.borrow_mut())?;
+    save_program_account(escrow_state_info, &escrow_state)?;
 
     msg!("Initialized escrow state: {:?}", escrow_state);
 
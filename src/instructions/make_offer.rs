@@ -3,30 +3,44 @@ use {
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
         account_info::AccountInfo,
+        clock::Clock,
         entrypoint::ProgramResult,
         program::{invoke, invoke_signed},
         program_error::ProgramError,
-        program_pack::Pack,
         pubkey::Pubkey,
         rent::Rent,
         system_instruction,
         sysvar::Sysvar,
     },
     spl_associated_token_account::instruction as associated_token_account_instruction,
-    spl_token::{instruction as token_instruction, state::Account as TokenAccount},
+    spl_token_2022::onchain::invoke_transfer_checked,
 };
 
 use crate::{
     errors::EscrowError,
-    state::Offer,
-    utils::{assert_is_associated_token_account, assert_token_account_mint_and_owner},
+    state::{EscrowState, Offer, ProgramAccount},
+    utils::{
+        assert_is_associated_token_account, assert_is_token_program_of_mint,
+        assert_token_account_mint_and_owner, get_mint_decimals, get_transfer_fee, is_native_mint,
+        load_fee_discount, load_fee_rates, load_program_account, pay_lamport_fee,
+        save_program_account, unpack_token_account, wrap_native,
+    },
 };
 
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
 pub struct MakeOfferArgs {
     pub id: u64,
+    /// Amount of token A sent to the vault, before any Token-2022 transfer fee
     pub token_a_offered_amount: u64,
     pub token_b_wanted_amount: u64,
+    /// Optional unix timestamp after which the offer can no longer be taken
+    pub expires_at: Option<i64>,
+    /// Optional taker to restrict the offer to (private offer)
+    pub allowed_taker: Option<Pubkey>,
+    /// Optional Merkle root of the takers to restrict the offer to (see `crate::merkle`)
+    pub taker_allowlist_root: Option<[u8; 32]>,
+    /// Whether the fee exemption of the maker is provided (see `EscrowInstruction::MakeOffer`)
+    pub has_fee_exemption: bool,
 }
 
 pub fn process(
@@ -36,6 +50,7 @@ pub fn process(
 ) -> ProgramResult {
     let [
         // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
         offer_info,
         token_a_mint,
         token_b_mint,
@@ -47,24 +62,89 @@ pub fn process(
         token_program,
         associated_token_program,
         system_program,
-        // res @ .. // NOTE: syntax for accepting dynamic length of accounts
+        fee_override_pair_info,
+        fee_override_token_a_info,
+        fee_override_token_b_info,
+        lamport_fee_vault,
+        remaining_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
+    // Split the optional account flagged in the instruction data from the transfer hook accounts
+    let (fee_exemption_info, remaining_accounts) = match remaining_accounts {
+        [fee_exemption_info, remaining_accounts @ ..] if args.has_fee_exemption => {
+            (Some(fee_exemption_info), remaining_accounts)
+        }
+        _ if args.has_fee_exemption => return Err(ProgramError::NotEnoughAccountKeys),
+        _ => (None, remaining_accounts),
+    };
+
     // Ensure the maker signs the instruction
     if !maker.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    let (offer, offer_address) = Offer::new(
+    // Validate the token program of the offered token
+    assert_is_token_program_of_mint(token_program, token_a_mint)?;
+
+    // Deserialize the escrow state and create program address
+    let escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure the provided escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure making offers is not paused
+    if escrow_state.is_make_paused {
+        return Err(EscrowError::MakePaused.into());
+    }
+
+    // Calculate token A amount to be escrowed after the Token-2022 transfer fee (if any)
+    let token_a_escrowed_amount = args
+        .token_a_offered_amount
+        .checked_sub(get_transfer_fee(token_a_mint, args.token_a_offered_amount)?)
+        .ok_or(EscrowError::MathError)?;
+
+    let (mut offer, offer_address) = Offer::new(
         program_id,
         args.id,
         *maker.key,
         *token_a_mint.key,
         *token_b_mint.key,
+        token_a_escrowed_amount,
         args.token_b_wanted_amount,
+        *payer.key,
     );
+    offer.expires_at = args.expires_at;
+    offer.allowed_taker = args.allowed_taker;
+    offer.taker_allowlist_root = args.taker_allowlist_root;
+
+    // Record the fee rates and fee rounding in effect, for offers taken under
+    // `FeePolicy::AtCreation`
+    let (fee_rates, fee_rounding) = load_fee_rates(
+        program_id,
+        &escrow_state,
+        [
+            fee_override_pair_info,
+            fee_override_token_a_info,
+            fee_override_token_b_info,
+        ],
+        token_a_mint.key,
+        token_b_mint.key,
+    )?;
+    offer.fee_rates = Some(fee_rates);
+    offer.fee_rounding = Some(fee_rounding);
+
+    // Record the fee discount of the maker (if any), for the fees levied when the offer is taken
+    offer.maker_fee_discount_bps = load_fee_discount(program_id, fee_exemption_info, maker.key)?;
+
+    // Ensure the offer does not expire immediately
+    if offer.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(EscrowError::OfferExpired.into());
+    }
 
     // Ensure the provided offer address is correct
     if *offer_info.key != offer_address {
@@ -78,21 +158,28 @@ pub fn process(
         &[offer.bump],
     ];
 
-    // Validate the owner and mint of the sending token A account
-    assert_token_account_mint_and_owner(maker_token_a_account, maker.key, token_a_mint.key)?;
+    // Validate the owner and mint of the sending token A account (unused for native SOL)
+    let is_token_a_native = is_native_mint(token_a_mint.key);
+    if !is_token_a_native {
+        assert_token_account_mint_and_owner(maker_token_a_account, maker.key, token_a_mint.key)?;
+    }
 
     // Validate the vault is owned by the offer account (ATA)
-    assert_is_associated_token_account(vault.key, offer_info.key, token_a_mint.key)?;
+    assert_is_associated_token_account(
+        vault.key,
+        offer_info.key,
+        token_a_mint.key,
+        token_program.key,
+    )?;
 
     // Create offer account
-    let size = borsh::to_vec::<Offer>(&offer)?.len();
-    let lamports_required = (Rent::get()?).minimum_balance(size);
+    let lamports_required = (Rent::get()?).minimum_balance(Offer::LEN);
     invoke_signed(
         &system_instruction::create_account(
             payer.key,
             offer_info.key,
             lamports_required,
-            size as u64,
+            Offer::LEN as u64,
             program_id,
         ),
         //   0. `[WRITE, SIGNER]` Funding account
@@ -130,33 +217,47 @@ pub fn process(
         ],
     )?;
 
-    // Transfer token A to vault
-    invoke(
-        &token_instruction::transfer(
-            token_program.key,
-            maker_token_a_account.key,
-            vault.key,
-            maker.key,
-            &[maker.key],
+    // Transfer token A to vault, wrapping SOL from the maker's wallet for the native mint
+    if is_token_a_native {
+        wrap_native(
+            token_program,
+            maker,
+            vault,
+            system_program,
             args.token_a_offered_amount,
-        )?,
-        //   0. `[writable]` The source account.
-        //   1. `[writable]` The destination account.
-        //   2. `[signer]` The source account's owner/delegate.
-        &[
+        )?;
+    } else {
+        let token_a_decimals = get_mint_decimals(token_a_mint)?;
+        invoke_transfer_checked(
+            token_program.key,
             maker_token_a_account.clone(),
+            token_a_mint.clone(),
             vault.clone(),
             maker.clone(),
-            token_program.clone(), // not required
-        ],
-    )?;
+            remaining_accounts,
+            args.token_a_offered_amount,
+            token_a_decimals,
+            &[],
+        )?;
+    }
 
-    let vault_token_amount = TokenAccount::unpack(&vault.data.borrow())?.amount;
+    // Ensure the vault holds exactly the escrowed amount
+    let vault_token_amount = unpack_token_account(vault)?.amount;
+    if vault_token_amount != token_a_escrowed_amount {
+        return Err(EscrowError::VaultBalanceMismatch.into());
+    }
 
-    assert_eq!(vault_token_amount, args.token_a_offered_amount);
+    // Pay the flat lamport fee of making an offer (if any) from the funding account
+    pay_lamport_fee(
+        program_id,
+        lamport_fee_vault,
+        payer,
+        system_program,
+        escrow_state.make_lamport_fee,
+    )?;
 
     // Write data into offer account
-    offer.serialize(&mut *offer_info.data.borrow_mut())?;
+    save_program_account(offer_info, &offer)?;
 
     Ok(())
 }
@@ -0,0 +1,95 @@
+//! Permissionless instruction to migrate an offer from the legacy layout.
+use {
+    borsh::BorshDeserialize,
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+use crate::{
+    errors::EscrowError,
+    state::{AccountType, LegacyOffer, Offer, ProgramAccount},
+    utils::{
+        assert_is_associated_token_account, realloc_program_account, save_program_account,
+        unpack_token_account,
+    },
+};
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        offer_info,
+        vault,
+        token_program,
+        payer,
+        system_program,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the offer is owned by the program, in the legacy layout
+    if offer_info.owner != program_id {
+        return Err(EscrowError::AccountOwnerMismatch.into());
+    }
+    if offer_info.data_len() != LegacyOffer::LEN {
+        return Err(EscrowError::AccountAlreadyMigrated.into());
+    }
+
+    // Deserialize the legacy offer
+    let legacy_offer = LegacyOffer::try_from_slice(&offer_info.data.borrow()[..])?;
+
+    // Create program address of the offer
+    let offer_address = Offer::create_program_address(
+        program_id,
+        &legacy_offer.maker,
+        legacy_offer.id,
+        legacy_offer.bump,
+    )?;
+
+    // Ensure the provided offer address is correct
+    if *offer_info.key != offer_address {
+        return Err(EscrowError::OfferKeyMismatch.into());
+    };
+
+    // Ensure the token program is either SPL Token or Token-2022, and owns the vault
+    if (*token_program.key != spl_token::ID && *token_program.key != spl_token_2022::ID)
+        || vault.owner != token_program.key
+    {
+        return Err(EscrowError::TokenProgramMismatch.into());
+    }
+
+    // Validate vault is owned by the offer account (ATA)
+    assert_is_associated_token_account(
+        vault.key,
+        offer_info.key,
+        &legacy_offer.token_a_mint,
+        token_program.key,
+    )?;
+
+    // NOTE: Legacy offers did not record their funder, the rent is refunded to the maker
+    let offer = Offer {
+        account_type: AccountType::Offer,
+        version: Offer::VERSION,
+        id: legacy_offer.id,
+        maker: legacy_offer.maker,
+        token_a_mint: legacy_offer.token_a_mint,
+        token_b_mint: legacy_offer.token_b_mint,
+        token_a_offered_amount: unpack_token_account(vault)?.amount,
+        token_b_wanted_amount: legacy_offer.token_b_wanted_amount,
+        expires_at: None,
+        allowed_taker: None,
+        taker_allowlist_root: None,
+        rent_payer: legacy_offer.maker,
+        bump: legacy_offer.bump,
+        fee_rates: None,
+        maker_fee_discount_bps: 0,
+        fee_rounding: None,
+    };
+
+    // Resize the account to the latest layout, and write data into it
+    realloc_program_account(offer_info, payer, system_program, Offer::LEN)?;
+    save_program_account(offer_info, &offer)?;
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! Instruction for manager to migrate the escrow state from the legacy layout.
+use {
+    borsh::BorshDeserialize,
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+use crate::{
+    errors::EscrowError,
+    state::{AccountType, EscrowState, FeePolicy, FeeRounding, LegacyEscrowState, ProgramAccount},
+    utils::{realloc_program_account, save_program_account},
+};
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        manager,
+        payer,
+        system_program,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the manager signs the instruction
+    if !manager.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Ensure the escrow state is owned by the program, in the legacy layout
+    if escrow_state_info.owner != program_id {
+        return Err(EscrowError::AccountOwnerMismatch.into());
+    }
+    if escrow_state_info.data_len() != LegacyEscrowState::LEN {
+        return Err(EscrowError::AccountAlreadyMigrated.into());
+    }
+
+    // Deserialize the legacy escrow state and create program address
+    let legacy_escrow_state =
+        LegacyEscrowState::try_from_slice(&escrow_state_info.data.borrow()[..])?;
+    let escrow_state_address =
+        EscrowState::create_program_address(program_id, legacy_escrow_state.bump)?;
+
+    // Ensure the provided escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided manager is authorized
+    if *manager.key != legacy_escrow_state.manager {
+        return Err(EscrowError::ManagerKeyUnauthorized.into());
+    }
+
+    let escrow_state = EscrowState {
+        account_type: AccountType::EscrowState,
+        version: EscrowState::VERSION,
+        manager: legacy_escrow_state.manager,
+        maker_fee_bps: legacy_escrow_state.maker_fee_bps,
+        taker_fee_bps: legacy_escrow_state.taker_fee_bps,
+        bump: legacy_escrow_state.bump,
+        pending_manager: None,
+        is_make_paused: false,
+        is_take_paused: false,
+        fee_policy: FeePolicy::AtTake,
+        pending_fees: None,
+        fee_change_delay: 0,
+        fee_collector: None,
+        fee_destination: None,
+        referral_share_bps: 0,
+        fee_rounding: FeeRounding::default(),
+        make_lamport_fee: 0,
+        take_lamport_fee: 0,
+        pending_fee_change_delay: None,
+    };
+
+    // Resize the account to the latest layout, and write data into it
+    realloc_program_account(escrow_state_info, payer, system_program, EscrowState::LEN)?;
+    save_program_account(escrow_state_info, &escrow_state)?;
+
+    msg!("Migrated escrow state: {:?}", escrow_state);
+
+    Ok(())
+}
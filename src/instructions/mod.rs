@@ -5,24 +5,61 @@ use {
         pubkey::Pubkey,
         system_program,
     },
-    spl_associated_token_account::get_associated_token_address,
+    spl_associated_token_account::get_associated_token_address_with_program_id,
+    spl_token_2022::offchain::{add_extra_account_metas, AccountDataResult, AccountFetchError},
+    std::future::Future,
 };
 
 use crate::{
     instructions::{
-        collect_fee::CollectFeeArgs, initialize::InitializeArgs, make_offer::MakeOfferArgs,
+        add_fee_exemption::AddFeeExemptionArgs,
+        collect_fee::CollectFeeArgs,
+        collect_fees::CollectFeesArgs,
+        create_fee_override::CreateFeeOverrideArgs,
+        initialize::InitializeArgs,
+        make_offer::MakeOfferArgs,
+        set_fee_change_delay::SetFeeChangeDelayArgs,
+        set_fee_collector::SetFeeCollectorArgs,
+        set_fee_policy::SetFeePolicyArgs,
         set_fees::SetFeesArgs,
+        set_lamport_fees::SetLamportFeesArgs,
+        set_pause::SetPauseArgs,
+        set_referral_share::SetReferralShareArgs,
+        take_offer::{PartialTakeOfferArgs, TakeOfferArgs},
+        update_fee_override::UpdateFeeOverrideArgs,
+        update_offer::UpdateOfferArgs,
     },
-    state::{EscrowState, Offer},
+    state::{EscrowState, FeeExemption, FeeOverride, LamportFeeVault, Offer},
+    utils::is_native_mint,
 };
 
+pub mod accept_manager;
+pub mod add_fee_exemption;
+pub mod cancel_manager_nomination;
 pub mod cancel_offer;
+pub mod cancel_pending_fees;
+pub mod close_fee_override;
 pub mod collect_fee;
+pub mod collect_fees;
+pub mod collect_lamport_fees;
+pub mod create_fee_override;
+pub mod expire_offer;
 pub mod initialize;
 pub mod make_offer;
+pub mod migrate_offer;
+pub mod migrate_state;
+pub mod remove_fee_exemption;
+pub mod set_fee_change_delay;
+pub mod set_fee_collector;
+pub mod set_fee_policy;
 pub mod set_fees;
+pub mod set_lamport_fees;
 pub mod set_manager;
+pub mod set_pause;
+pub mod set_referral_share;
 pub mod take_offer;
+pub mod update_fee_override;
+pub mod update_offer;
 
 /// Declares all available instructions of the escrow program.
 ///
@@ -40,13 +77,14 @@ pub enum EscrowInstruction {
     // 2. `[]` System program
     Initialize(InitializeArgs),
 
-    // Set programwide fees (maker fee and taker fee) in the program state
+    // Announce programwide fees (maker fee and taker fee) in the program state, in effect after
+    // the fee change delay (see `SetFeeChangeDelay`)
     //
     // 0. `[writeable]` (PDA) Escrow state account
     // 1. `[signer]` Manager
     SetFees(SetFeesArgs),
 
-    // Set programwide manager in the program state
+    // Nominate a new programwide manager in the program state (see `AcceptManager`)
     //
     // 0. `[writeable]` (PDA) Escrow state account
     // 1. `[signer]` Manager
@@ -56,34 +94,45 @@ pub enum EscrowInstruction {
     // Collect tokens in escrow fee account
     //
     // 0. `[]` (PDA) Escrow state account
-    // 1. `[signer(,writeable)]` Manager (writeable if `should_close_fee_account` is set)
-    // 2. `[]` Mint account for escrow fee
+    // 1. `[signer(,writeable)]` Manager or fee collector (writeable if `should_close_fee_account` is set)
+    // 2. `[(writeable)]` Mint account for escrow fee (writeable for Token-2022 if `should_close_fee_account` is set)
     // 3. `[writeable]` Source escrow fee account (ATA of escrow state)
-    // 4. `[writeable]` Destination token account
-    // 5. `[]` SPL Token program
+    // 4. `[writeable]` Destination token account (ATA of the fee destination, if set)
+    // 5. `[]` Token program of the mint (SPL Token or Token-2022)
+    // 6.. `[]` Extra accounts required by the transfer hook of the mint (Token-2022), if any
     CollectFee(CollectFeeArgs),
 
     // User-facing instructions
 
     // Make escrow offer
     //
-    // 0. `[writeable]` (PDA) Escrow offer account to be initialized
-    // 1. `[]` Token A (maker's token) mint account for the escrow offer
-    // 2. `[]` Token B (taker's token) mint account for the escrow offer
-    // 3. `[writeable]` Maker's token A account for the escrow offer
-    // 4. `[writeable]` (PDA) Escrow offer's vault token account (Token A, ATA of Offer account)
-    // 5. `[signer]` Maker's wallet address
-    // 6. `[writeable,signer]` Funding account
-    // 7. `[]` SPL Token program
-    // 8. `[]` SPL Associated Token Account program
-    // 9. `[]` System program
+    // 0. `[]` (PDA) Escrow state account
+    // 1. `[writeable]` (PDA) Escrow offer account to be initialized
+    // 2. `[]` Token A (maker's token) mint account for the escrow offer
+    // 3. `[]` Token B (taker's token) mint account for the escrow offer
+    // 4. `[writeable]` Maker's token A account for the escrow offer
+    // 5. `[writeable]` (PDA) Escrow offer's vault token account (Token A, ATA of Offer account)
+    // 6. `[signer(,writeable)]` Maker's wallet address (writeable to wrap SOL if token A is native)
+    // 7. `[writeable,signer]` Funding account
+    // 8. `[]` Token program of token A (SPL Token or Token-2022)
+    // 9. `[]` SPL Associated Token Account program
+    // 10. `[]` System program
+    // 11. `[]` (PDA) Fee override for the mint pair of the offer (may be uninitialized)
+    // 12. `[]` (PDA) Fee override for token A (may be uninitialized)
+    // 13. `[]` (PDA) Fee override for token B (may be uninitialized)
+    // 14. `[writeable]` (PDA) Lamport fee vault, receiving the make lamport fee (if any)
+    //
+    // Followed by the optional account flagged in the instruction data:
+    //
+    // - `[]` (PDA) Fee exemption of the maker, only if `has_fee_exemption` is set
+    // - `..` `[]` Extra accounts required by the transfer hook of token A (Token-2022), if any
     MakeOffer(MakeOfferArgs),
 
     // Take escrow offer
     //
     // 0. `[]` (PDA) Escrow state account
     // 1. `[writeable]` (PDA) Escrow offer account to be taken
-    // 2. `[]` Token A (maker's token) mint account for the escrow offer
+    // 2. `[(writeable)]` Token A (maker's token) mint account for the escrow offer (writeable for Token-2022)
     // 3. `[]` Token B (taker's token) mint account for the escrow offer
     // 4. `[writeable]` Maker's token B account to receive from taker (ATA)
     // 5. `[writeable]` Taker's token A account to receive from vault (ATA)
@@ -91,26 +140,229 @@ pub enum EscrowInstruction {
     // 7. `[writeable]` Escrow state's Token A account for fee collection (ATA of Escrow state)
     // 8. `[writeable]` Escrow state's Token B account for fee collection (ATA of Escrow state)
     // 9. `[writeable]` (PDA) Escrow offer's vault token account (Token A, ATA of Offer account)
-    // 10. `[]` Maker's wallet address
-    // 11. `[signer]` Taker's wallet address
-    // 12. `[writeable,signer]` Funding account
-    // 13. `[]` SPL Token program
-    // 14. `[]` SPL Associated Token Account program
-    // 15. `[]` System program
-    TakeOffer,
+    // 10. `[(writeable)]` Maker's wallet address (writeable to receive SOL if token B is native)
+    // 11. `[signer(,writeable)]` Taker's wallet address (writeable to receive or wrap SOL if token A or B is native)
+    // 12. `[writeable]` Original funding account of the offer to refund rent to
+    // 13. `[writeable,signer]` Funding account
+    // 14. `[]` Token program of token A (SPL Token or Token-2022)
+    // 15. `[]` Token program of token B (SPL Token or Token-2022)
+    // 16. `[]` SPL Associated Token Account program
+    // 17. `[]` System program
+    // 18. `[]` (PDA) Fee override for the mint pair of the offer (may be uninitialized)
+    // 19. `[]` (PDA) Fee override for token A (may be uninitialized)
+    // 20. `[]` (PDA) Fee override for token B (may be uninitialized)
+    // 21. `[writeable]` (PDA) Lamport fee vault, receiving the take lamport fee (if any)
+    //
+    // Followed by the optional accounts flagged in the instruction data, in order:
+    //
+    // - `[]` (PDA) Fee exemption of the taker, only if `has_fee_exemption` is set
+    // - `[]` Referrer's wallet address to share fees with (not the taker or the maker), only if `referrer` is set
+    // - `[writeable]` Referrer's token A account for fee sharing (ATA), only if `referrer` is set
+    // - `[writeable]` Referrer's token B account for fee sharing (ATA), only if `referrer` is set
+    // - `..` `[]` Extra accounts required by the transfer hooks of token A and B (Token-2022), if any
+    TakeOffer(TakeOfferArgs),
 
     // Cancel escrow offer
     //
     // 0. `[writeable]` (PDA) Escrow offer account to be canceled
-    // 1. `[]` Token A (maker's token) mint account for the escrow offer
+    // 1. `[(writeable)]` Token A (maker's token) mint account for the escrow offer (writeable for Token-2022)
     // 2. `[writeable]` Maker's token A account to refund to (Token A, ATA)
     // 3. `[writeable]` (PDA) Escrow offer's vault token account (Token A, ATA of Offer account)
-    // 4. `[signer]` Maker's wallet address
-    // 5. `[writeable,signer]` Funding account
-    // 6. `[]` SPL Token program
-    // 7. `[]` SPL Associated Token Account program
-    // 8. `[]` System program
+    // 4. `[signer(,writeable)]` Maker's wallet address (writeable to receive SOL if token A is native)
+    // 5. `[writeable]` Original funding account of the offer to refund rent to
+    // 6. `[writeable,signer]` Funding account
+    // 7. `[]` Token program of token A (SPL Token or Token-2022)
+    // 8. `[]` SPL Associated Token Account program
+    // 9. `[]` System program
+    // 10.. `[]` Extra accounts required by the transfer hook of token A (Token-2022), if any
     CancelOffer,
+
+    // Take part of an escrow offer, paying a pro-rata amount of token B
+    //
+    // The offer is closed once the vault is fully drained.
+    // Expects the same accounts as `TakeOffer`.
+    PartialTakeOffer(PartialTakeOfferArgs),
+
+    // Close an expired escrow offer (permissionless)
+    //
+    // 0. `[writeable]` (PDA) Escrow offer account to be closed
+    // 1. `[(writeable)]` Token A (maker's token) mint account for the escrow offer (writeable for Token-2022)
+    // 2. `[writeable]` Maker's token A account to refund to (Token A, ATA)
+    // 3. `[writeable]` (PDA) Escrow offer's vault token account (Token A, ATA of Offer account)
+    // 4. `[writeable]` Maker's wallet address (refunded in SOL for the native mint)
+    // 5. `[writeable]` Original funding account of the offer to refund rent to
+    // 6. `[writeable,signer]` Funding account
+    // 7. `[]` Token program of token A (SPL Token or Token-2022)
+    // 8. `[]` SPL Associated Token Account program
+    // 9. `[]` System program
+    // 10.. `[]` Extra accounts required by the transfer hook of token A (Token-2022), if any
+    ExpireOffer,
+
+    // Update escrow offer in place (wanted amount, deposit or withdraw token A)
+    //
+    // 0. `[writeable]` (PDA) Escrow offer account to be updated
+    // 1. `[]` Token A (maker's token) mint account for the escrow offer
+    // 2. `[writeable]` Maker's token A account to deposit from or withdraw to
+    // 3. `[writeable]` (PDA) Escrow offer's vault token account (Token A, ATA of Offer account)
+    // 4. `[signer]` Maker's wallet address
+    // 5. `[]` Token program of token A (SPL Token or Token-2022)
+    // 6.. `[]` Extra accounts required by the transfer hook of token A (Token-2022), if any
+    UpdateOffer(UpdateOfferArgs),
+
+    // Migrate the program state (Escrow State) from the legacy layout to the latest one
+    //
+    // 0. `[writeable]` (PDA) Escrow state account
+    // 1. `[signer]` Manager
+    // 2. `[writeable,signer]` Funding account (for the rent of the resized account)
+    // 3. `[]` System program
+    MigrateState,
+
+    // Migrate an escrow offer from the legacy layout to the latest one (permissionless)
+    //
+    // 0. `[writeable]` (PDA) Escrow offer account
+    // 1. `[]` (PDA) Escrow offer's vault token account (Token A, ATA of Offer account)
+    // 2. `[]` Token program of token A (SPL Token or Token-2022)
+    // 3. `[writeable,signer]` Funding account (for the rent of the resized account)
+    // 4. `[]` System program
+    MigrateOffer,
+
+    // Accept the nomination as programwide manager in the program state
+    //
+    // 0. `[writeable]` (PDA) Escrow state account
+    // 1. `[signer]` New manager (nominated with `SetManager`)
+    AcceptManager,
+
+    // Cancel the nomination of a new programwide manager in the program state
+    //
+    // 0. `[writeable]` (PDA) Escrow state account
+    // 1. `[signer]` Manager
+    CancelManagerNomination,
+
+    // Pause or resume making and taking offers (canceling offers is always allowed)
+    //
+    // 0. `[writeable]` (PDA) Escrow state account
+    // 1. `[signer]` Manager
+    SetPause(SetPauseArgs),
+
+    // Create fee rates overriding the programwide fees for a mint pair, or for a single mint
+    //
+    // In effect immediately, regardless of the fee change delay (see `SetFeeChangeDelay`).
+    //
+    // 0. `[]` (PDA) Escrow state account
+    // 1. `[writeable]` (PDA) Fee override account to be initialized
+    // 2. `[signer]` Manager
+    // 3. `[writeable,signer]` Funding account
+    // 4. `[]` System program
+    CreateFeeOverride(CreateFeeOverrideArgs),
+
+    // Update the fee rates of a fee override
+    //
+    // In effect immediately, regardless of the fee change delay (see `SetFeeChangeDelay`).
+    //
+    // 0. `[]` (PDA) Escrow state account
+    // 1. `[writeable]` (PDA) Fee override account
+    // 2. `[signer]` Manager
+    UpdateFeeOverride(UpdateFeeOverrideArgs),
+
+    // Close a fee override, restoring the programwide fees for its mints
+    //
+    // In effect immediately, regardless of the fee change delay (see `SetFeeChangeDelay`).
+    //
+    // 0. `[]` (PDA) Escrow state account
+    // 1. `[writeable]` (PDA) Fee override account to be closed
+    // 2. `[writeable,signer]` Manager (rent destination)
+    // 3. `[]` System program
+    CloseFeeOverride,
+
+    // Set whether offers are taken at the fee rates of their creation or of their take
+    //
+    // 0. `[writeable]` (PDA) Escrow state account
+    // 1. `[signer]` Manager
+    SetFeePolicy(SetFeePolicyArgs),
+
+    // Set the minimum delay between announcing programwide fees and their effect
+    //
+    // A decreased delay is in effect only once the current delay has passed.
+    //
+    // NOTE: The delay only applies to the programwide fees announced with `SetFees`. Fee overrides
+    // (`CreateFeeOverride`, `UpdateFeeOverride` and `CloseFeeOverride`), the referral share
+    // (`SetReferralShare`) and the lamport fees (`SetLamportFees`) are in effect immediately.
+    //
+    // 0. `[writeable]` (PDA) Escrow state account
+    // 1. `[signer]` Manager
+    SetFeeChangeDelay(SetFeeChangeDelayArgs),
+
+    // Retract the programwide fees announced with `SetFees`, before they are in effect
+    //
+    // 0. `[writeable]` (PDA) Escrow state account
+    // 1. `[signer]` Manager
+    CancelPendingFees,
+
+    // Set the fee collector allowed to collect fees besides the manager, and the owner of the
+    // only token accounts fees can be collected to
+    //
+    // 0. `[writeable]` (PDA) Escrow state account
+    // 1. `[signer]` Manager
+    SetFeeCollector(SetFeeCollectorArgs),
+
+    // Set the share of fees sent to the referrer of a take, in basis points
+    //
+    // In effect immediately, regardless of the fee change delay (see `SetFeeChangeDelay`).
+    //
+    // 0. `[writeable]` (PDA) Escrow state account
+    // 1. `[signer]` Manager
+    SetReferralShare(SetReferralShareArgs),
+
+    // Create a discount of the fees of a wallet as maker or taker, optionally until an expiry
+    //
+    // 0. `[]` (PDA) Escrow state account
+    // 1. `[writeable]` (PDA) Fee exemption account to be initialized
+    // 2. `[signer]` Manager
+    // 3. `[writeable,signer]` Funding account
+    // 4. `[]` System program
+    AddFeeExemption(AddFeeExemptionArgs),
+
+    // Close the fee exemption of a wallet (offers already made keep the discount of the maker)
+    //
+    // 0. `[]` (PDA) Escrow state account
+    // 1. `[writeable]` (PDA) Fee exemption account to be closed
+    // 2. `[writeable,signer]` Manager (rent destination)
+    // 3. `[]` System program
+    RemoveFeeExemption,
+
+    // Set the flat lamport fees paid by the funding account per offer made and per take, creating
+    // the lamport fee vault if needed
+    //
+    // In effect immediately, regardless of the fee change delay (see `SetFeeChangeDelay`).
+    //
+    // 0. `[writeable]` (PDA) Escrow state account
+    // 1. `[signer]` Manager
+    // 2. `[writeable]` (PDA) Lamport fee vault account (initialized if needed)
+    // 3. `[writeable,signer]` Funding account
+    // 4. `[]` System program
+    SetLamportFees(SetLamportFeesArgs),
+
+    // Collect the lamports accumulated in the lamport fee vault, above its rent
+    //
+    // 0. `[]` (PDA) Escrow state account
+    // 1. `[signer]` Manager or fee collector
+    // 2. `[writeable]` (PDA) Lamport fee vault account
+    // 3. `[writeable]` Destination account (the fee destination, if set)
+    CollectLamportFees,
+
+    // Collect tokens in many escrow fee accounts at once
+    //
+    // 0. `[]` (PDA) Escrow state account
+    // 1. `[signer(,writeable)]` Manager or fee collector (writeable if `should_close_fee_accounts` is set)
+    // 2. `[]` SPL Token program
+    // 3. `[]` Token-2022 program
+    // 4.. Triples of accounts, one per mint:
+    //   0. `[(writeable)]` Mint account for escrow fee (writeable for Token-2022 if `should_close_fee_accounts` is set)
+    //   1. `[writeable]` Source escrow fee account (ATA of escrow state)
+    //   2. `[writeable]` Destination token account (ATA of the fee destination, if set)
+    //
+    // Mints with a transfer hook (Token-2022) are not supported, use `CollectFee` instead.
+    CollectFees(CollectFeesArgs),
 }
 
 //
@@ -148,6 +400,67 @@ pub fn set_fees_ix(manager_pubkey: &Pubkey, set_fees_args: SetFeesArgs) -> Instr
     Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
 }
 
+pub fn set_fee_change_delay_ix(
+    manager_pubkey: &Pubkey,
+    set_fee_change_delay_args: SetFeeChangeDelayArgs,
+) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+
+    let accounts = vec![
+        AccountMeta::new(escrow_state_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+    ];
+
+    let instruction_data = EscrowInstruction::SetFeeChangeDelay(set_fee_change_delay_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn set_fee_collector_ix(
+    manager_pubkey: &Pubkey,
+    set_fee_collector_args: SetFeeCollectorArgs,
+) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+
+    let accounts = vec![
+        AccountMeta::new(escrow_state_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+    ];
+
+    let instruction_data = EscrowInstruction::SetFeeCollector(set_fee_collector_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn set_referral_share_ix(
+    manager_pubkey: &Pubkey,
+    set_referral_share_args: SetReferralShareArgs,
+) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+
+    let accounts = vec![
+        AccountMeta::new(escrow_state_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+    ];
+
+    let instruction_data = EscrowInstruction::SetReferralShare(set_referral_share_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn cancel_pending_fees_ix(manager_pubkey: &Pubkey) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+
+    let accounts = vec![
+        AccountMeta::new(escrow_state_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+    ];
+
+    let instruction_data = EscrowInstruction::CancelPendingFees {};
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
 pub fn set_manager_ix(manager_pubkey: &Pubkey, new_manager_pubkey: &Pubkey) -> Instruction {
     let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
 
@@ -162,29 +475,276 @@ pub fn set_manager_ix(manager_pubkey: &Pubkey, new_manager_pubkey: &Pubkey) -> I
     Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
 }
 
-pub fn collect_fee_ix(
+pub fn set_pause_ix(manager_pubkey: &Pubkey, set_pause_args: SetPauseArgs) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+
+    let accounts = vec![
+        AccountMeta::new(escrow_state_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+    ];
+
+    let instruction_data = EscrowInstruction::SetPause(set_pause_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn set_fee_policy_ix(
+    manager_pubkey: &Pubkey,
+    set_fee_policy_args: SetFeePolicyArgs,
+) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+
+    let accounts = vec![
+        AccountMeta::new(escrow_state_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+    ];
+
+    let instruction_data = EscrowInstruction::SetFeePolicy(set_fee_policy_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn accept_manager_ix(new_manager_pubkey: &Pubkey) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+
+    let accounts = vec![
+        AccountMeta::new(escrow_state_address, false),
+        AccountMeta::new_readonly(*new_manager_pubkey, true),
+    ];
+
+    let instruction_data = EscrowInstruction::AcceptManager {};
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn cancel_manager_nomination_ix(manager_pubkey: &Pubkey) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+
+    let accounts = vec![
+        AccountMeta::new(escrow_state_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+    ];
+
+    let instruction_data = EscrowInstruction::CancelManagerNomination {};
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn create_fee_override_ix(
+    manager_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    create_fee_override_args: CreateFeeOverrideArgs,
+) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+    let (fee_override_address, _) = FeeOverride::find_program_address(
+        &crate::ID,
+        &create_fee_override_args.mint,
+        create_fee_override_args.paired_mint.as_ref(),
+    );
+
+    let accounts = vec![
+        AccountMeta::new_readonly(escrow_state_address, false),
+        AccountMeta::new(fee_override_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+        AccountMeta::new(*payer_pubkey, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+
+    let instruction_data = EscrowInstruction::CreateFeeOverride(create_fee_override_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn update_fee_override_ix(
+    manager_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    paired_mint_pubkey: Option<&Pubkey>,
+    update_fee_override_args: UpdateFeeOverrideArgs,
+) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+    let (fee_override_address, _) =
+        FeeOverride::find_program_address(&crate::ID, mint_pubkey, paired_mint_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(escrow_state_address, false),
+        AccountMeta::new(fee_override_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+    ];
+
+    let instruction_data = EscrowInstruction::UpdateFeeOverride(update_fee_override_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn close_fee_override_ix(
     manager_pubkey: &Pubkey,
     mint_pubkey: &Pubkey,
+    paired_mint_pubkey: Option<&Pubkey>,
+) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+    let (fee_override_address, _) =
+        FeeOverride::find_program_address(&crate::ID, mint_pubkey, paired_mint_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(escrow_state_address, false),
+        AccountMeta::new(fee_override_address, false),
+        AccountMeta::new(*manager_pubkey, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+
+    let instruction_data = EscrowInstruction::CloseFeeOverride {};
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn add_fee_exemption_ix(
+    manager_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    add_fee_exemption_args: AddFeeExemptionArgs,
+) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+    let (fee_exemption_address, _) =
+        FeeExemption::find_program_address(&crate::ID, &add_fee_exemption_args.wallet);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(escrow_state_address, false),
+        AccountMeta::new(fee_exemption_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+        AccountMeta::new(*payer_pubkey, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+
+    let instruction_data = EscrowInstruction::AddFeeExemption(add_fee_exemption_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn set_lamport_fees_ix(
+    manager_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    set_lamport_fees_args: SetLamportFeesArgs,
+) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+    let (lamport_fee_vault_address, _) = LamportFeeVault::find_program_address(&crate::ID);
+
+    let accounts = vec![
+        AccountMeta::new(escrow_state_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+        AccountMeta::new(lamport_fee_vault_address, false),
+        AccountMeta::new(*payer_pubkey, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+
+    let instruction_data = EscrowInstruction::SetLamportFees(set_lamport_fees_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn collect_lamport_fees_ix(
+    authority_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+    let (lamport_fee_vault_address, _) = LamportFeeVault::find_program_address(&crate::ID);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(escrow_state_address, false),
+        AccountMeta::new_readonly(*authority_pubkey, true),
+        AccountMeta::new(lamport_fee_vault_address, false),
+        AccountMeta::new(*destination_pubkey, false),
+    ];
+
+    let instruction_data = EscrowInstruction::CollectLamportFees {};
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn remove_fee_exemption_ix(manager_pubkey: &Pubkey, wallet_pubkey: &Pubkey) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+    let (fee_exemption_address, _) = FeeExemption::find_program_address(&crate::ID, wallet_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(escrow_state_address, false),
+        AccountMeta::new(fee_exemption_address, false),
+        AccountMeta::new(*manager_pubkey, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+
+    let instruction_data = EscrowInstruction::RemoveFeeExemption {};
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+/// Wallets are writeable only when native SOL is wrapped from or unwrapped into them.
+fn wallet_account_meta(pubkey: &Pubkey, is_signer: bool, is_native: bool) -> AccountMeta {
+    if is_native {
+        AccountMeta::new(*pubkey, is_signer)
+    } else {
+        AccountMeta::new_readonly(*pubkey, is_signer)
+    }
+}
+
+/// Fee overrides that may apply to an offer of the given mints, in order of precedence: for the
+/// mint pair, for token A and for token B (whether initialized or not).
+fn fee_override_account_metas(
+    token_a_mint_pubkey: &Pubkey,
+    token_b_mint_pubkey: &Pubkey,
+) -> [AccountMeta; 3] {
+    [
+        (token_a_mint_pubkey, Some(token_b_mint_pubkey)),
+        (token_a_mint_pubkey, None),
+        (token_b_mint_pubkey, None),
+    ]
+    .map(|(mint_pubkey, paired_mint_pubkey)| {
+        let (fee_override_address, _) =
+            FeeOverride::find_program_address(&crate::ID, mint_pubkey, paired_mint_pubkey);
+        AccountMeta::new_readonly(fee_override_address, false)
+    })
+}
+
+/// Token-2022 mints are writeable, so that withheld transfer fees can be harvested to them
+/// before closing a token account.
+fn token_mint_account_meta(mint_pubkey: &Pubkey, token_program_pubkey: &Pubkey) -> AccountMeta {
+    if *token_program_pubkey == spl_token_2022::ID {
+        AccountMeta::new(*mint_pubkey, false)
+    } else {
+        AccountMeta::new_readonly(*mint_pubkey, false)
+    }
+}
+
+pub fn collect_fee_ix(
+    authority_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    token_program_pubkey: &Pubkey,
     destination_token_account_pubkey: &Pubkey,
     collect_fee_args: CollectFeeArgs,
 ) -> Instruction {
     let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
 
-    let escrow_fee_account = get_associated_token_address(&escrow_state_address, mint_pubkey);
+    let escrow_fee_account = get_associated_token_address_with_program_id(
+        &escrow_state_address,
+        mint_pubkey,
+        token_program_pubkey,
+    );
 
-    let manager_account_meta = if collect_fee_args.should_close_fee_account {
+    let authority_account_meta = if collect_fee_args.should_close_fee_account {
         // Set to writeable (rent destination)
-        AccountMeta::new(*manager_pubkey, true)
+        AccountMeta::new(*authority_pubkey, true)
+    } else {
+        AccountMeta::new_readonly(*authority_pubkey, true)
+    };
+    let mint_account_meta = if collect_fee_args.should_close_fee_account {
+        token_mint_account_meta(mint_pubkey, token_program_pubkey)
     } else {
-        AccountMeta::new_readonly(*manager_pubkey, true)
+        AccountMeta::new_readonly(*mint_pubkey, false)
     };
     let accounts = vec![
         AccountMeta::new_readonly(escrow_state_address, false),
-        manager_account_meta,
-        AccountMeta::new_readonly(*mint_pubkey, false),
+        authority_account_meta,
+        mint_account_meta,
         AccountMeta::new(escrow_fee_account, false),
         AccountMeta::new(*destination_token_account_pubkey, false),
-        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(*token_program_pubkey, false),
     ];
 
     let instruction_data = EscrowInstruction::CollectFee(collect_fee_args);
@@ -192,63 +752,254 @@ pub fn collect_fee_ix(
     Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
 }
 
+/// Maximum number of mints per `CollectFees` instruction built with `collect_fees_ixs`, so that
+/// each instruction fits a transaction signed by a fee payer and the authority.
+pub const MAX_COLLECT_FEES_MINTS: usize = 8;
+
+/// Builds `CollectFees` instruction for the given (mint, token program) pairs, collecting to
+/// the token accounts (ATA) of the destination wallet.
+///
+/// NOTE: Mints with a transfer hook are rejected, collect their fees with
+/// `collect_fee_ix_with_extra_metas` instead.
+pub fn collect_fees_ix(
+    authority_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    mints: &[(Pubkey, Pubkey)],
+    collect_fees_args: CollectFeesArgs,
+) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+
+    let authority_account_meta = if collect_fees_args.should_close_fee_accounts {
+        // Set to writeable (rent destination)
+        AccountMeta::new(*authority_pubkey, true)
+    } else {
+        AccountMeta::new_readonly(*authority_pubkey, true)
+    };
+    let mut accounts = vec![
+        AccountMeta::new_readonly(escrow_state_address, false),
+        authority_account_meta,
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(spl_token_2022::ID, false),
+    ];
+    for (mint_pubkey, token_program_pubkey) in mints {
+        let mint_account_meta = if collect_fees_args.should_close_fee_accounts {
+            token_mint_account_meta(mint_pubkey, token_program_pubkey)
+        } else {
+            AccountMeta::new_readonly(*mint_pubkey, false)
+        };
+        accounts.extend([
+            mint_account_meta,
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(
+                    &escrow_state_address,
+                    mint_pubkey,
+                    token_program_pubkey,
+                ),
+                false,
+            ),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(
+                    destination_pubkey,
+                    mint_pubkey,
+                    token_program_pubkey,
+                ),
+                false,
+            ),
+        ]);
+    }
+
+    let instruction_data = EscrowInstruction::CollectFees(collect_fees_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+/// Builds `CollectFees` instructions for any number of (mint, token program) pairs, chunked by
+/// `MAX_COLLECT_FEES_MINTS` to be sent in separate transactions.
+pub fn collect_fees_ixs(
+    authority_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    mints: &[(Pubkey, Pubkey)],
+    collect_fees_args: CollectFeesArgs,
+) -> Vec<Instruction> {
+    mints
+        .chunks(MAX_COLLECT_FEES_MINTS)
+        .map(|mints| {
+            collect_fees_ix(
+                authority_pubkey,
+                destination_pubkey,
+                mints,
+                collect_fees_args,
+            )
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn make_offer_ix(
     maker_pubkey: &Pubkey,
     maker_token_a_account_pubkey: &Pubkey,
     token_a_mint_pubkey: &Pubkey,
     token_b_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
     payer_pubkey: &Pubkey,
     make_offer_args: MakeOfferArgs,
 ) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
     let (offer_address, _) =
         Offer::find_program_address(&crate::ID, maker_pubkey, make_offer_args.id);
+    let (lamport_fee_vault_address, _) = LamportFeeVault::find_program_address(&crate::ID);
 
-    let vault_pubkey = get_associated_token_address(&offer_address, token_a_mint_pubkey);
+    let vault_pubkey = get_associated_token_address_with_program_id(
+        &offer_address,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+    );
 
-    let accounts = vec![
+    let mut accounts = vec![
+        AccountMeta::new_readonly(escrow_state_address, false),
         AccountMeta::new(offer_address, false),
         AccountMeta::new_readonly(*token_a_mint_pubkey, false),
         AccountMeta::new_readonly(*token_b_mint_pubkey, false),
         AccountMeta::new(*maker_token_a_account_pubkey, false),
         AccountMeta::new(vault_pubkey, false),
-        AccountMeta::new_readonly(*maker_pubkey, true),
+        wallet_account_meta(maker_pubkey, true, is_native_mint(token_a_mint_pubkey)),
         AccountMeta::new(*payer_pubkey, true),
-        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(*token_a_program_pubkey, false),
         AccountMeta::new_readonly(spl_associated_token_account::ID, false),
         AccountMeta::new_readonly(system_program::ID, false),
     ];
+    accounts.extend(fee_override_account_metas(
+        token_a_mint_pubkey,
+        token_b_mint_pubkey,
+    ));
+    accounts.push(AccountMeta::new(lamport_fee_vault_address, false));
+    if make_offer_args.has_fee_exemption {
+        let (fee_exemption_address, _) =
+            FeeExemption::find_program_address(&crate::ID, maker_pubkey);
+        accounts.push(AccountMeta::new_readonly(fee_exemption_address, false));
+    }
     let instruction_data = EscrowInstruction::MakeOffer(make_offer_args);
 
     Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn take_offer_ix(
     offer_pubkey: &Pubkey,
     token_a_mint_pubkey: &Pubkey,
     token_b_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    token_b_program_pubkey: &Pubkey,
     // maker_token_b_account_pubkey: &Pubkey, // use ATA
     // taker_token_a_account_pubkey: &Pubkey, // use ATA
     taker_token_b_account_pubkey: &Pubkey,
     maker_pubkey: &Pubkey,
     taker_pubkey: &Pubkey,
+    rent_payer_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    take_offer_args: TakeOfferArgs,
+) -> Instruction {
+    let accounts = take_offer_accounts(
+        offer_pubkey,
+        token_a_mint_pubkey,
+        token_b_mint_pubkey,
+        token_a_program_pubkey,
+        token_b_program_pubkey,
+        taker_token_b_account_pubkey,
+        maker_pubkey,
+        taker_pubkey,
+        rent_payer_pubkey,
+        payer_pubkey,
+        take_offer_args.has_fee_exemption,
+        take_offer_args.referrer.as_ref(),
+    );
+    let instruction_data = EscrowInstruction::TakeOffer(take_offer_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn partial_take_offer_ix(
+    offer_pubkey: &Pubkey,
+    token_a_mint_pubkey: &Pubkey,
+    token_b_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    token_b_program_pubkey: &Pubkey,
+    taker_token_b_account_pubkey: &Pubkey,
+    maker_pubkey: &Pubkey,
+    taker_pubkey: &Pubkey,
+    rent_payer_pubkey: &Pubkey,
     payer_pubkey: &Pubkey,
+    partial_take_offer_args: PartialTakeOfferArgs,
 ) -> Instruction {
+    let accounts = take_offer_accounts(
+        offer_pubkey,
+        token_a_mint_pubkey,
+        token_b_mint_pubkey,
+        token_a_program_pubkey,
+        token_b_program_pubkey,
+        taker_token_b_account_pubkey,
+        maker_pubkey,
+        taker_pubkey,
+        rent_payer_pubkey,
+        payer_pubkey,
+        partial_take_offer_args.has_fee_exemption,
+        partial_take_offer_args.referrer.as_ref(),
+    );
+    let instruction_data = EscrowInstruction::PartialTakeOffer(partial_take_offer_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+/// Accounts shared by `TakeOffer` and `PartialTakeOffer`
+#[allow(clippy::too_many_arguments)]
+fn take_offer_accounts(
+    offer_pubkey: &Pubkey,
+    token_a_mint_pubkey: &Pubkey,
+    token_b_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    token_b_program_pubkey: &Pubkey,
+    taker_token_b_account_pubkey: &Pubkey,
+    maker_pubkey: &Pubkey,
+    taker_pubkey: &Pubkey,
+    rent_payer_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    has_fee_exemption: bool,
+    referrer_pubkey: Option<&Pubkey>,
+) -> Vec<AccountMeta> {
     let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+    let (lamport_fee_vault_address, _) = LamportFeeVault::find_program_address(&crate::ID);
 
-    let vault_pubkey = get_associated_token_address(offer_pubkey, token_a_mint_pubkey);
-    let maker_token_b_account_pubkey =
-        get_associated_token_address(maker_pubkey, token_b_mint_pubkey);
-    let taker_token_a_account_pubkey =
-        get_associated_token_address(taker_pubkey, token_a_mint_pubkey);
-    let escrow_fee_token_a_account_pubkey =
-        get_associated_token_address(&escrow_state_address, token_a_mint_pubkey);
-    let escrow_fee_token_b_account_pubkey =
-        get_associated_token_address(&escrow_state_address, token_b_mint_pubkey);
+    let vault_pubkey = get_associated_token_address_with_program_id(
+        offer_pubkey,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+    );
+    let maker_token_b_account_pubkey = get_associated_token_address_with_program_id(
+        maker_pubkey,
+        token_b_mint_pubkey,
+        token_b_program_pubkey,
+    );
+    let taker_token_a_account_pubkey = get_associated_token_address_with_program_id(
+        taker_pubkey,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+    );
+    let escrow_fee_token_a_account_pubkey = get_associated_token_address_with_program_id(
+        &escrow_state_address,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+    );
+    let escrow_fee_token_b_account_pubkey = get_associated_token_address_with_program_id(
+        &escrow_state_address,
+        token_b_mint_pubkey,
+        token_b_program_pubkey,
+    );
 
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new_readonly(escrow_state_address, false),
         AccountMeta::new(*offer_pubkey, false),
-        AccountMeta::new_readonly(*token_a_mint_pubkey, false),
+        token_mint_account_meta(token_a_mint_pubkey, token_a_program_pubkey),
         AccountMeta::new_readonly(*token_b_mint_pubkey, false),
         AccountMeta::new(maker_token_b_account_pubkey, false),
         AccountMeta::new(taker_token_a_account_pubkey, false),
@@ -256,39 +1007,85 @@ pub fn take_offer_ix(
         AccountMeta::new(escrow_fee_token_a_account_pubkey, false),
         AccountMeta::new(escrow_fee_token_b_account_pubkey, false),
         AccountMeta::new(vault_pubkey, false),
-        AccountMeta::new_readonly(*maker_pubkey, false),
-        AccountMeta::new_readonly(*taker_pubkey, true),
+        wallet_account_meta(maker_pubkey, false, is_native_mint(token_b_mint_pubkey)),
+        wallet_account_meta(
+            taker_pubkey,
+            true,
+            is_native_mint(token_a_mint_pubkey) || is_native_mint(token_b_mint_pubkey),
+        ),
+        AccountMeta::new(*rent_payer_pubkey, false),
         AccountMeta::new(*payer_pubkey, true),
-        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(*token_a_program_pubkey, false),
+        AccountMeta::new_readonly(*token_b_program_pubkey, false),
         AccountMeta::new_readonly(spl_associated_token_account::ID, false),
         AccountMeta::new_readonly(system_program::ID, false),
     ];
-    let instruction_data = EscrowInstruction::TakeOffer {};
+    accounts.extend(fee_override_account_metas(
+        token_a_mint_pubkey,
+        token_b_mint_pubkey,
+    ));
+    accounts.push(AccountMeta::new(lamport_fee_vault_address, false));
 
-    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+    // Optional accounts, as flagged in the instruction data
+    if has_fee_exemption {
+        let (fee_exemption_address, _) =
+            FeeExemption::find_program_address(&crate::ID, taker_pubkey);
+        accounts.push(AccountMeta::new_readonly(fee_exemption_address, false));
+    }
+    if let Some(referrer_pubkey) = referrer_pubkey {
+        accounts.push(AccountMeta::new_readonly(*referrer_pubkey, false));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address_with_program_id(
+                referrer_pubkey,
+                token_a_mint_pubkey,
+                token_a_program_pubkey,
+            ),
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address_with_program_id(
+                referrer_pubkey,
+                token_b_mint_pubkey,
+                token_b_program_pubkey,
+            ),
+            false,
+        ));
+    }
+
+    accounts
 }
 
 pub fn cancel_offer_ix(
     maker_pubkey: &Pubkey,
     // maker_token_a_account_pubkey: &Pubkey, // use ATA
     token_a_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    rent_payer_pubkey: &Pubkey,
     payer_pubkey: &Pubkey,
     offer_id: u64,
 ) -> Instruction {
     let (offer_address, _) = Offer::find_program_address(&crate::ID, maker_pubkey, offer_id);
 
-    let maker_token_a_account_pubkey =
-        get_associated_token_address(maker_pubkey, token_a_mint_pubkey);
-    let vault_pubkey = get_associated_token_address(&offer_address, token_a_mint_pubkey);
+    let maker_token_a_account_pubkey = get_associated_token_address_with_program_id(
+        maker_pubkey,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+    );
+    let vault_pubkey = get_associated_token_address_with_program_id(
+        &offer_address,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+    );
 
     let accounts = vec![
         AccountMeta::new(offer_address, false),
-        AccountMeta::new_readonly(*token_a_mint_pubkey, false),
+        token_mint_account_meta(token_a_mint_pubkey, token_a_program_pubkey),
         AccountMeta::new(maker_token_a_account_pubkey, false),
         AccountMeta::new(vault_pubkey, false),
-        AccountMeta::new_readonly(*maker_pubkey, true),
+        wallet_account_meta(maker_pubkey, true, is_native_mint(token_a_mint_pubkey)),
+        AccountMeta::new(*rent_payer_pubkey, false),
         AccountMeta::new(*payer_pubkey, true),
-        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(*token_a_program_pubkey, false),
         AccountMeta::new_readonly(spl_associated_token_account::ID, false),
         AccountMeta::new_readonly(system_program::ID, false),
     ];
@@ -296,3 +1093,499 @@ pub fn cancel_offer_ix(
 
     Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
 }
+
+pub fn expire_offer_ix(
+    maker_pubkey: &Pubkey,
+    // maker_token_a_account_pubkey: &Pubkey, // use ATA
+    token_a_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    rent_payer_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    offer_id: u64,
+) -> Instruction {
+    let (offer_address, _) = Offer::find_program_address(&crate::ID, maker_pubkey, offer_id);
+
+    let maker_token_a_account_pubkey = get_associated_token_address_with_program_id(
+        maker_pubkey,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+    );
+    let vault_pubkey = get_associated_token_address_with_program_id(
+        &offer_address,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+    );
+
+    let accounts = vec![
+        AccountMeta::new(offer_address, false),
+        token_mint_account_meta(token_a_mint_pubkey, token_a_program_pubkey),
+        AccountMeta::new(maker_token_a_account_pubkey, false),
+        AccountMeta::new(vault_pubkey, false),
+        AccountMeta::new(*maker_pubkey, false),
+        AccountMeta::new(*rent_payer_pubkey, false),
+        AccountMeta::new(*payer_pubkey, true),
+        AccountMeta::new_readonly(*token_a_program_pubkey, false),
+        AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    let instruction_data = EscrowInstruction::ExpireOffer {};
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn update_offer_ix(
+    maker_pubkey: &Pubkey,
+    maker_token_a_account_pubkey: &Pubkey,
+    token_a_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    offer_id: u64,
+    update_offer_args: UpdateOfferArgs,
+) -> Instruction {
+    let (offer_address, _) = Offer::find_program_address(&crate::ID, maker_pubkey, offer_id);
+
+    let vault_pubkey = get_associated_token_address_with_program_id(
+        &offer_address,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+    );
+
+    let accounts = vec![
+        AccountMeta::new(offer_address, false),
+        AccountMeta::new_readonly(*token_a_mint_pubkey, false),
+        AccountMeta::new(*maker_token_a_account_pubkey, false),
+        AccountMeta::new(vault_pubkey, false),
+        AccountMeta::new_readonly(*maker_pubkey, true),
+        AccountMeta::new_readonly(*token_a_program_pubkey, false),
+    ];
+    let instruction_data = EscrowInstruction::UpdateOffer(update_offer_args);
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn migrate_state_ix(manager_pubkey: &Pubkey, payer_pubkey: &Pubkey) -> Instruction {
+    let (escrow_state_address, _) = EscrowState::find_program_address(&crate::ID);
+
+    let accounts = vec![
+        AccountMeta::new(escrow_state_address, false),
+        AccountMeta::new_readonly(*manager_pubkey, true),
+        AccountMeta::new(*payer_pubkey, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    let instruction_data = EscrowInstruction::MigrateState {};
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+pub fn migrate_offer_ix(
+    offer_pubkey: &Pubkey,
+    token_a_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+) -> Instruction {
+    let vault_pubkey = get_associated_token_address_with_program_id(
+        offer_pubkey,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+    );
+
+    let accounts = vec![
+        AccountMeta::new(*offer_pubkey, false),
+        AccountMeta::new_readonly(vault_pubkey, false),
+        AccountMeta::new_readonly(*token_a_program_pubkey, false),
+        AccountMeta::new(*payer_pubkey, true),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    let instruction_data = EscrowInstruction::MigrateOffer {};
+
+    Instruction::new_with_borsh(crate::ID, &instruction_data, accounts)
+}
+
+/// Builds `MakeOffer` instruction with the extra accounts required by the transfer hook of
+/// token A (if any), resolved with the given account data fetcher.
+#[allow(clippy::too_many_arguments)]
+pub async fn make_offer_ix_with_extra_metas<F, Fut>(
+    maker_pubkey: &Pubkey,
+    maker_token_a_account_pubkey: &Pubkey,
+    token_a_mint_pubkey: &Pubkey,
+    token_b_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    make_offer_args: MakeOfferArgs,
+    fetch_account_data_fn: F,
+) -> Result<Instruction, AccountFetchError>
+where
+    F: Fn(Pubkey) -> Fut,
+    Fut: Future<Output = AccountDataResult>,
+{
+    let token_a_offered_amount = make_offer_args.token_a_offered_amount;
+    let mut instruction = make_offer_ix(
+        maker_pubkey,
+        maker_token_a_account_pubkey,
+        token_a_mint_pubkey,
+        token_b_mint_pubkey,
+        token_a_program_pubkey,
+        payer_pubkey,
+        make_offer_args,
+    );
+
+    // Token A from maker to vault (see `make_offer_ix`)
+    let transfers = [HookedTransfer {
+        token_program: *token_a_program_pubkey,
+        source: *maker_token_a_account_pubkey,
+        mint: *token_a_mint_pubkey,
+        destination: instruction.accounts[5].pubkey,
+        authority: *maker_pubkey,
+        amount: token_a_offered_amount,
+    }];
+    add_transfer_hook_extra_metas(&mut instruction, &transfers, fetch_account_data_fn).await?;
+
+    Ok(instruction)
+}
+
+/// Builds `TakeOffer` instruction with the extra accounts required by the transfer hooks of
+/// token A and token B (if any), resolved with the given account data fetcher.
+#[allow(clippy::too_many_arguments)]
+pub async fn take_offer_ix_with_extra_metas<F, Fut>(
+    offer_pubkey: &Pubkey,
+    token_a_mint_pubkey: &Pubkey,
+    token_b_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    token_b_program_pubkey: &Pubkey,
+    taker_token_b_account_pubkey: &Pubkey,
+    maker_pubkey: &Pubkey,
+    taker_pubkey: &Pubkey,
+    rent_payer_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    take_offer_args: TakeOfferArgs,
+    fetch_account_data_fn: F,
+) -> Result<Instruction, AccountFetchError>
+where
+    F: Fn(Pubkey) -> Fut,
+    Fut: Future<Output = AccountDataResult>,
+{
+    let referrer_pubkey = take_offer_args.referrer;
+    let mut instruction = take_offer_ix(
+        offer_pubkey,
+        token_a_mint_pubkey,
+        token_b_mint_pubkey,
+        token_a_program_pubkey,
+        token_b_program_pubkey,
+        taker_token_b_account_pubkey,
+        maker_pubkey,
+        taker_pubkey,
+        rent_payer_pubkey,
+        payer_pubkey,
+        take_offer_args,
+    );
+
+    let transfers = take_offer_transfers(&instruction, referrer_pubkey.as_ref());
+    add_transfer_hook_extra_metas(&mut instruction, &transfers, fetch_account_data_fn).await?;
+
+    Ok(instruction)
+}
+
+/// Builds `PartialTakeOffer` instruction with the extra accounts required by the transfer hooks
+/// of token A and token B (if any), resolved with the given account data fetcher.
+#[allow(clippy::too_many_arguments)]
+pub async fn partial_take_offer_ix_with_extra_metas<F, Fut>(
+    offer_pubkey: &Pubkey,
+    token_a_mint_pubkey: &Pubkey,
+    token_b_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    token_b_program_pubkey: &Pubkey,
+    taker_token_b_account_pubkey: &Pubkey,
+    maker_pubkey: &Pubkey,
+    taker_pubkey: &Pubkey,
+    rent_payer_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    partial_take_offer_args: PartialTakeOfferArgs,
+    fetch_account_data_fn: F,
+) -> Result<Instruction, AccountFetchError>
+where
+    F: Fn(Pubkey) -> Fut,
+    Fut: Future<Output = AccountDataResult>,
+{
+    let referrer_pubkey = partial_take_offer_args.referrer;
+    let mut instruction = partial_take_offer_ix(
+        offer_pubkey,
+        token_a_mint_pubkey,
+        token_b_mint_pubkey,
+        token_a_program_pubkey,
+        token_b_program_pubkey,
+        taker_token_b_account_pubkey,
+        maker_pubkey,
+        taker_pubkey,
+        rent_payer_pubkey,
+        payer_pubkey,
+        partial_take_offer_args,
+    );
+
+    let transfers = take_offer_transfers(&instruction, referrer_pubkey.as_ref());
+    add_transfer_hook_extra_metas(&mut instruction, &transfers, fetch_account_data_fn).await?;
+
+    Ok(instruction)
+}
+
+/// Builds `CancelOffer` instruction with the extra accounts required by the transfer hook of
+/// token A (if any), resolved with the given account data fetcher.
+pub async fn cancel_offer_ix_with_extra_metas<F, Fut>(
+    maker_pubkey: &Pubkey,
+    token_a_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    rent_payer_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    offer_id: u64,
+    fetch_account_data_fn: F,
+) -> Result<Instruction, AccountFetchError>
+where
+    F: Fn(Pubkey) -> Fut,
+    Fut: Future<Output = AccountDataResult>,
+{
+    let mut instruction = cancel_offer_ix(
+        maker_pubkey,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+        rent_payer_pubkey,
+        payer_pubkey,
+        offer_id,
+    );
+
+    // Token A from vault to maker (see `cancel_offer_ix`)
+    let transfers = [HookedTransfer {
+        token_program: *token_a_program_pubkey,
+        source: instruction.accounts[3].pubkey,
+        mint: *token_a_mint_pubkey,
+        destination: instruction.accounts[2].pubkey,
+        authority: instruction.accounts[0].pubkey,
+        amount: 0,
+    }];
+    add_transfer_hook_extra_metas(&mut instruction, &transfers, fetch_account_data_fn).await?;
+
+    Ok(instruction)
+}
+
+/// Builds `ExpireOffer` instruction with the extra accounts required by the transfer hook of
+/// token A (if any), resolved with the given account data fetcher.
+pub async fn expire_offer_ix_with_extra_metas<F, Fut>(
+    maker_pubkey: &Pubkey,
+    token_a_mint_pubkey: &Pubkey,
+    token_a_program_pubkey: &Pubkey,
+    rent_payer_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    offer_id: u64,
+    fetch_account_data_fn: F,
+) -> Result<Instruction, AccountFetchError>
+where
+    F: Fn(Pubkey) -> Fut,
+    Fut: Future<Output = AccountDataResult>,
+{
+    let mut instruction = expire_offer_ix(
+        maker_pubkey,
+        token_a_mint_pubkey,
+        token_a_program_pubkey,
+        rent_payer_pubkey,
+        payer_pubkey,
+        offer_id,
+    );
+
+    // Token A from vault to maker (see `expire_offer_ix`)
+    let transfers = [HookedTransfer {
+        token_program: *token_a_program_pubkey,
+        source: instruction.accounts[3].pubkey,
+        mint: *token_a_mint_pubkey,
+        destination: instruction.accounts[2].pubkey,
+        authority: instruction.accounts[0].pubkey,
+        amount: 0,
+    }];
+    add_transfer_hook_extra_metas(&mut instruction, &transfers, fetch_account_data_fn).await?;
+
+    Ok(instruction)
+}
+
+/// Builds `CollectFee` instruction with the extra accounts required by the transfer hook of the
+/// mint (if any), resolved with the given account data fetcher.
+pub async fn collect_fee_ix_with_extra_metas<F, Fut>(
+    authority_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    token_program_pubkey: &Pubkey,
+    destination_token_account_pubkey: &Pubkey,
+    collect_fee_args: CollectFeeArgs,
+    fetch_account_data_fn: F,
+) -> Result<Instruction, AccountFetchError>
+where
+    F: Fn(Pubkey) -> Fut,
+    Fut: Future<Output = AccountDataResult>,
+{
+    let mut instruction = collect_fee_ix(
+        authority_pubkey,
+        mint_pubkey,
+        token_program_pubkey,
+        destination_token_account_pubkey,
+        collect_fee_args,
+    );
+
+    // Escrow fee from escrow fee account to destination (see `collect_fee_ix`)
+    let transfers = [HookedTransfer {
+        token_program: *token_program_pubkey,
+        source: instruction.accounts[3].pubkey,
+        mint: *mint_pubkey,
+        destination: *destination_token_account_pubkey,
+        authority: instruction.accounts[0].pubkey,
+        amount: 0,
+    }];
+    add_transfer_hook_extra_metas(&mut instruction, &transfers, fetch_account_data_fn).await?;
+
+    Ok(instruction)
+}
+
+/// A token transfer made by the escrow program, which may require transfer hook accounts.
+struct HookedTransfer {
+    token_program: Pubkey,
+    source: Pubkey,
+    mint: Pubkey,
+    destination: Pubkey,
+    authority: Pubkey,
+    /// NOTE: Only known upfront when making an offer, hooks depending on it are unsupported
+    amount: u64,
+}
+
+/// Transfers made by `TakeOffer` and `PartialTakeOffer` (see `take_offer_accounts`)
+fn take_offer_transfers(
+    instruction: &Instruction,
+    referrer_pubkey: Option<&Pubkey>,
+) -> Vec<HookedTransfer> {
+    let key = |index: usize| instruction.accounts[index].pubkey;
+    let (offer, token_a_mint, token_b_mint) = (key(1), key(2), key(3));
+    let (maker_token_b_account, taker_token_a_account, taker_token_b_account) =
+        (key(4), key(5), key(6));
+    let (escrow_fee_token_a_account, escrow_fee_token_b_account) = (key(7), key(8));
+    let (vault, taker) = (key(9), key(11));
+    let (token_a_program, token_b_program) = (key(14), key(15));
+
+    let mut transfers = vec![
+        // Token B from taker to escrow fee account, and to maker
+        HookedTransfer {
+            token_program: token_b_program,
+            source: taker_token_b_account,
+            mint: token_b_mint,
+            destination: escrow_fee_token_b_account,
+            authority: taker,
+            amount: 0,
+        },
+        HookedTransfer {
+            token_program: token_b_program,
+            source: taker_token_b_account,
+            mint: token_b_mint,
+            destination: maker_token_b_account,
+            authority: taker,
+            amount: 0,
+        },
+        // Token A from vault to escrow fee account, and to taker
+        HookedTransfer {
+            token_program: token_a_program,
+            source: vault,
+            mint: token_a_mint,
+            destination: escrow_fee_token_a_account,
+            authority: offer,
+            amount: 0,
+        },
+        HookedTransfer {
+            token_program: token_a_program,
+            source: vault,
+            mint: token_a_mint,
+            destination: taker_token_a_account,
+            authority: offer,
+            amount: 0,
+        },
+    ];
+
+    // Referral share of fees from taker and vault to referrer, if any
+    if let Some(referrer_pubkey) = referrer_pubkey {
+        transfers.push(HookedTransfer {
+            token_program: token_b_program,
+            source: taker_token_b_account,
+            mint: token_b_mint,
+            destination: get_associated_token_address_with_program_id(
+                referrer_pubkey,
+                &token_b_mint,
+                &token_b_program,
+            ),
+            authority: taker,
+            amount: 0,
+        });
+        transfers.push(HookedTransfer {
+            token_program: token_a_program,
+            source: vault,
+            mint: token_a_mint,
+            destination: get_associated_token_address_with_program_id(
+                referrer_pubkey,
+                &token_a_mint,
+                &token_a_program,
+            ),
+            authority: offer,
+            amount: 0,
+        });
+    }
+
+    transfers
+}
+
+/// Resolves the extra accounts required by the transfer hooks (if any) of the given transfers,
+/// and appends them to the instruction as remaining accounts.
+async fn add_transfer_hook_extra_metas<F, Fut>(
+    instruction: &mut Instruction,
+    transfers: &[HookedTransfer],
+    fetch_account_data_fn: F,
+) -> Result<(), AccountFetchError>
+where
+    F: Fn(Pubkey) -> Fut,
+    Fut: Future<Output = AccountDataResult>,
+{
+    let mut extra_account_metas: Vec<AccountMeta> = vec![];
+
+    for transfer in transfers {
+        // Only Token-2022 mints can carry a transfer hook
+        if transfer.token_program != spl_token_2022::ID {
+            continue;
+        }
+
+        let mut transfer_instruction = spl_token_2022::instruction::transfer_checked(
+            &transfer.token_program,
+            &transfer.source,
+            &transfer.mint,
+            &transfer.destination,
+            &transfer.authority,
+            &[],
+            transfer.amount,
+            0, // NB: decimals are irrelevant to account resolution
+        )?;
+        add_extra_account_metas(
+            &mut transfer_instruction,
+            &transfer.source,
+            &transfer.mint,
+            &transfer.destination,
+            &transfer.authority,
+            transfer.amount,
+            &fetch_account_data_fn,
+        )
+        .await?;
+
+        // Skip the accounts of the transfer itself, and merge duplicated extra accounts
+        for account_meta in transfer_instruction.accounts.drain(4..) {
+            match extra_account_metas
+                .iter_mut()
+                .find(|extra_account_meta| extra_account_meta.pubkey == account_meta.pubkey)
+            {
+                Some(extra_account_meta) => {
+                    extra_account_meta.is_signer |= account_meta.is_signer;
+                    extra_account_meta.is_writable |= account_meta.is_writable;
+                }
+                None => extra_account_metas.push(account_meta),
+            }
+        }
+    }
+
+    instruction.accounts.extend(extra_account_metas);
+
+    Ok(())
+}
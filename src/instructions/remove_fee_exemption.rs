@@ -0,0 +1,65 @@
+//! Instruction for manager to remove the fee exemption of a wallet.
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    errors::EscrowError,
+    state::{EscrowState, FeeExemption},
+    utils::load_program_account,
+};
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        fee_exemption_info,
+        manager,
+        system_program,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the manager signs the instruction
+    if !manager.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize the escrow state and create program address
+    let escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure the provided escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided manager is authorized
+    if *manager.key != escrow_state.manager {
+        return Err(EscrowError::ManagerKeyUnauthorized.into());
+    }
+
+    // Deserialize the fee exemption
+    let fee_exemption = load_program_account::<FeeExemption>(program_id, fee_exemption_info)?;
+
+    // Ensure the provided fee exemption address is correct
+    if *fee_exemption_info.key != fee_exemption.create_program_address(program_id)? {
+        return Err(EscrowError::FeeExemptionKeyMismatch.into());
+    };
+
+    // Send the rent to the manager
+    let lamports = fee_exemption_info.lamports();
+    **fee_exemption_info.lamports.borrow_mut() -= lamports;
+    **manager.lamports.borrow_mut() += lamports;
+
+    // Realloc the account to zero
+    fee_exemption_info.realloc(0, true)?;
+
+    // Assign the account to the System Program
+    fee_exemption_info.assign(system_program.key);
+
+    solana_program::msg!("Removed fee exemption: {:?}", fee_exemption);
+
+    Ok(())
+}
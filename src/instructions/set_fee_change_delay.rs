@@ -0,0 +1,85 @@
+//! Instruction for manager to set the minimum delay between announcing escrow fees and their
+//! effect.
+//!
+//! The delay only applies to the programwide fees announced with `SetFees`, while fee overrides,
+//! the referral share and the lamport fees are in effect as soon as they are set.
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+        program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+    },
+};
+
+use crate::{
+    errors::EscrowError,
+    state::{EscrowState, PendingFeeChangeDelay},
+    utils::{load_program_account, save_program_account},
+};
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct SetFeeChangeDelayArgs {
+    /// Minimum delay in seconds, in effect only once the current delay has passed if decreased
+    pub fee_change_delay: u32,
+}
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetFeeChangeDelayArgs,
+) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        manager,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the manager signs the instruction
+    if !manager.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize the escrow state and create program address
+    let mut escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure the provided escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided manager is authorized
+    if *manager.key != escrow_state.manager {
+        return Err(EscrowError::ManagerKeyUnauthorized.into());
+    }
+
+    // Apply the previously decreased fee change delay first, if already in effect
+    let unix_timestamp = Clock::get()?.unix_timestamp;
+    escrow_state.apply_pending_fee_change_delay(unix_timestamp);
+
+    // Increase the delay right away, but decrease it only once the current delay has passed, so
+    // that fee changes cannot be announced earlier than the current delay allows
+    if args.fee_change_delay >= escrow_state.fee_change_delay {
+        escrow_state.fee_change_delay = args.fee_change_delay;
+        escrow_state.pending_fee_change_delay = None;
+    } else {
+        escrow_state.pending_fee_change_delay = Some(PendingFeeChangeDelay {
+            fee_change_delay: args.fee_change_delay,
+            effective_at: unix_timestamp
+                .checked_add(i64::from(escrow_state.fee_change_delay))
+                .ok_or(EscrowError::MathError)?,
+        });
+    }
+
+    // Write data into escrow state account
+    save_program_account(escrow_state_info, &escrow_state)?;
+
+    solana_program::msg!(
+        "Set fee change delay in the escrow state: {:?}",
+        escrow_state
+    );
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+//! Instruction for manager to set the fee collector, and the owner fees can be collected to.
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+use crate::{
+    errors::EscrowError,
+    state::EscrowState,
+    utils::{load_program_account, save_program_account},
+};
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct SetFeeCollectorArgs {
+    /// Authority allowed to collect fees besides the manager, none if `None`
+    pub fee_collector: Option<Pubkey>,
+    /// Owner of the only token accounts (ATA) fees can be collected to, any if `None`
+    pub fee_destination: Option<Pubkey>,
+}
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetFeeCollectorArgs,
+) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        manager,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the manager signs the instruction
+    if !manager.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize the escrow state and create program address
+    let mut escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure the provided escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided manager is authorized
+    if *manager.key != escrow_state.manager {
+        return Err(EscrowError::ManagerKeyUnauthorized.into());
+    }
+
+    escrow_state.fee_collector = args.fee_collector;
+    escrow_state.fee_destination = args.fee_destination;
+
+    // Write data into escrow state account
+    save_program_account(escrow_state_info, &escrow_state)?;
+
+    solana_program::msg!("Set fee collector in the escrow state: {:?}", escrow_state);
+
+    Ok(())
+}
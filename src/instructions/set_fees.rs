@@ -1,18 +1,27 @@
-//! Instruction for manager to update escrow fees.
+//! Instruction for manager to announce new escrow fees, in effect after the fee change delay.
 use {
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
-        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
-        pubkey::Pubkey,
+        account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+        program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
     },
 };
 
-use crate::{errors::EscrowError, state::EscrowState, utils::assert_is_bps_in_range};
+use crate::{
+    errors::EscrowError,
+    state::{EscrowState, FeeRates, FeeRounding, PendingFees},
+    utils::{assert_is_bps_in_range, load_program_account, save_program_account},
+};
 
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
 pub struct SetFeesArgs {
     pub maker_fee_bps: u16,
     pub taker_fee_bps: u16,
+    /// Unix timestamp from which the fees are in effect, as early as the fee change delay
+    /// allows if `None`
+    pub effective_at: Option<i64>,
+    /// Rounding and minimum of the fee amounts, in effect along with the fees
+    pub fee_rounding: FeeRounding,
 }
 
 pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], args: SetFeesArgs) -> ProgramResult {
@@ -34,7 +43,7 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], args: SetFeesArgs)
     }
 
     // Deserialize the escrow state and create program address
-    let mut escrow_state = EscrowState::try_from_slice(&escrow_state_info.data.borrow()[..])?;
+    let mut escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
     let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
 
     // Ensure the provided escrow state address is correct
@@ -47,11 +56,33 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], args: SetFeesArgs)
         return Err(EscrowError::ManagerKeyUnauthorized.into());
     }
 
-    escrow_state.maker_fee_bps = args.maker_fee_bps;
-    escrow_state.taker_fee_bps = args.taker_fee_bps;
+    // Apply the previously announced fees and fee change delay first, if already in effect
+    let unix_timestamp = Clock::get()?.unix_timestamp;
+    escrow_state.apply_pending_fees(unix_timestamp);
+    escrow_state.apply_pending_fee_change_delay(unix_timestamp);
+
+    // Ensure the fees are announced at least the fee change delay ahead
+    let earliest_effective_at = unix_timestamp
+        .checked_add(i64::from(escrow_state.fee_change_delay))
+        .ok_or(EscrowError::MathError)?;
+    let effective_at = args.effective_at.unwrap_or(earliest_effective_at);
+    if effective_at < earliest_effective_at {
+        return Err(EscrowError::FeeChangeTooEarly.into());
+    }
+
+    // Announce the fees, replacing any pending ones (applied right away if already in effect)
+    escrow_state.pending_fees = Some(PendingFees {
+        fee_rates: FeeRates {
+            maker_fee_bps: args.maker_fee_bps,
+            taker_fee_bps: args.taker_fee_bps,
+        },
+        effective_at,
+        fee_rounding: args.fee_rounding,
+    });
+    escrow_state.apply_pending_fees(unix_timestamp);
 
     // Write data into escrow state account
-    escrow_state.serialize(&mut *escrow_state_info.data.borrow_mut())?;
+    save_program_account(escrow_state_info, &escrow_state)?;
 
     solana_program::msg!("Set fees in the escrow state: {:?}", escrow_state);
 
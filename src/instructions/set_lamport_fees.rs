@@ -0,0 +1,92 @@
+//! Instruction for manager to set the flat lamport fees of making and taking offers, creating
+//! the lamport fee vault on first use.
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+use crate::{
+    errors::EscrowError,
+    state::{EscrowState, LamportFeeVault, ProgramAccount},
+    utils::{create_program_account, load_program_account, save_program_account},
+};
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct SetLamportFeesArgs {
+    /// Flat fee per offer made, in lamports (none if zero)
+    pub make_lamport_fee: u64,
+    /// Flat fee per take, in lamports (none if zero)
+    pub take_lamport_fee: u64,
+}
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: SetLamportFeesArgs,
+) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        manager,
+        lamport_fee_vault_info,
+        payer,
+        system_program,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the manager signs the instruction
+    if !manager.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize the escrow state and create program address
+    let mut escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure the provided escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided manager is authorized
+    if *manager.key != escrow_state.manager {
+        return Err(EscrowError::ManagerKeyUnauthorized.into());
+    }
+
+    // Prepare the lamport fee vault and its address
+    let (lamport_fee_vault, lamport_fee_vault_address) = LamportFeeVault::new(program_id);
+
+    // Ensure the provided lamport fee vault address is correct
+    if *lamport_fee_vault_info.key != lamport_fee_vault_address {
+        return Err(EscrowError::LamportFeeVaultKeyMismatch.into());
+    }
+
+    // Create lamport fee vault account, unless already created (it may be funded beforehand)
+    if lamport_fee_vault_info.owner != program_id {
+        create_program_account(
+            program_id,
+            lamport_fee_vault_info,
+            payer,
+            system_program,
+            LamportFeeVault::LEN,
+            &[LamportFeeVault::SEED, &[lamport_fee_vault.bump]],
+        )?;
+
+        // Write data into lamport fee vault account
+        save_program_account(lamport_fee_vault_info, &lamport_fee_vault)?;
+    }
+
+    escrow_state.make_lamport_fee = args.make_lamport_fee;
+    escrow_state.take_lamport_fee = args.take_lamport_fee;
+
+    // Write data into escrow state account
+    save_program_account(escrow_state_info, &escrow_state)?;
+
+    solana_program::msg!("Set lamport fees in the escrow state: {:?}", escrow_state);
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-//! Instruction for manager to transfer the authority.
+//! Instruction for manager to nominate a new manager (see `accept_manager`).
 use {
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
@@ -7,7 +7,11 @@ use {
     },
 };
 
-use crate::{errors::EscrowError, state::EscrowState};
+use crate::{
+    errors::EscrowError,
+    state::EscrowState,
+    utils::{load_program_account, save_program_account},
+};
 
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
 pub struct SetManagerArgs {}
@@ -28,7 +32,7 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     }
 
     // Deserialize the escrow state create program address
-    let mut escrow_state = EscrowState::try_from_slice(&escrow_state_info.data.borrow()[..])?;
+    let mut escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
     let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
 
     // Ensure the provided escrow state address is correct
@@ -46,12 +50,13 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         return Err(EscrowError::ManagerKeyAlreadySet.into());
     }
 
-    escrow_state.manager = *new_manager.key;
+    // NOTE: The authority is only transferred once the new manager accepts it
+    escrow_state.pending_manager = Some(*new_manager.key);
 
     // Write data into escrow state account
-    escrow_state.serialize(&mut *escrow_state_info.data.borrow_mut())?;
+    save_program_account(escrow_state_info, &escrow_state)?;
 
-    solana_program::msg!("Set manager in the escrow state: {:?}", escrow_state);
+    solana_program::msg!("Nominated manager in the escrow state: {:?}", escrow_state);
 
     Ok(())
 }
@@ -3,26 +3,96 @@ use {
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
         account_info::AccountInfo,
+        clock::Clock,
         entrypoint::ProgramResult,
         program::{invoke, invoke_signed},
         program_error::ProgramError,
-        program_pack::Pack,
         pubkey::Pubkey,
+        system_instruction,
+        sysvar::Sysvar,
     },
     spl_associated_token_account::instruction as associated_token_account_instruction,
-    spl_token::{instruction as token_instruction, state::Account as TokenAccount},
+    spl_token_2022::{instruction as token_instruction, onchain::invoke_transfer_checked},
 };
 
 use crate::{
     errors::EscrowError,
-    state::{EscrowState, Offer},
-    utils::assert_is_associated_token_account,
+    state::{EscrowState, FeePolicy, Offer},
+    utils::{
+        assert_is_associated_token_account, assert_is_token_program_of_mint,
+        get_amount_with_transfer_fee, get_mint_decimals, get_transfer_fee,
+        harvest_withheld_transfer_fees, is_native_mint, load_fee_discount, load_fee_rates,
+        load_program_account, pay_lamport_fee, save_program_account, unpack_token_account,
+        unwrap_native, wrap_native,
+    },
 };
 
+#[derive(BorshDeserialize, BorshSerialize, Debug, Default)]
+pub struct TakeOfferArgs {
+    /// Merkle proof of the taker, if the offer has a taker allowlist (see `crate::merkle`)
+    pub taker_allowlist_proof: Vec<[u8; 32]>,
+    /// Maximum amount of token B sent by the taker, inclusive of fees
+    pub max_token_b_to_pay: Option<u64>,
+    /// Minimum amount of token A received by the taker, net of fees
+    pub min_token_a_to_receive: Option<u64>,
+    /// Whether the fee exemption of the taker is provided (see `EscrowInstruction::TakeOffer`)
+    pub has_fee_exemption: bool,
+    /// Optional referrer to share fees with, whose accounts are then provided
+    pub referrer: Option<Pubkey>,
+}
+
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
-pub struct TakeOfferArgs {}
+pub struct PartialTakeOfferArgs {
+    /// Amount of token A to take from the vault, before the taker fee
+    pub token_a_amount: u64,
+    /// Merkle proof of the taker, if the offer has a taker allowlist (see `crate::merkle`)
+    pub taker_allowlist_proof: Vec<[u8; 32]>,
+    /// Maximum amount of token B sent by the taker, inclusive of fees
+    pub max_token_b_to_pay: Option<u64>,
+    /// Minimum amount of token A received by the taker, net of fees
+    pub min_token_a_to_receive: Option<u64>,
+    /// Whether the fee exemption of the taker is provided (see `EscrowInstruction::TakeOffer`)
+    pub has_fee_exemption: bool,
+    /// Optional referrer to share fees with, whose accounts are then provided
+    pub referrer: Option<Pubkey>,
+}
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: TakeOfferArgs,
+) -> ProgramResult {
+    take(program_id, accounts, None, args)
+}
+
+pub fn process_partial(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: PartialTakeOfferArgs,
+) -> ProgramResult {
+    take(
+        program_id,
+        accounts,
+        Some(args.token_a_amount),
+        TakeOfferArgs {
+            taker_allowlist_proof: args.taker_allowlist_proof,
+            max_token_b_to_pay: args.max_token_b_to_pay,
+            min_token_a_to_receive: args.min_token_a_to_receive,
+            has_fee_exemption: args.has_fee_exemption,
+            referrer: args.referrer,
+        },
+    )
+}
 
-pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+/// Takes the given amount of token A from the offer, or the whole vault if `None`.
+///
+/// The offer and its vault are closed only when the vault is fully drained.
+fn take(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    token_a_amount: Option<u64>,
+    args: TakeOfferArgs,
+) -> ProgramResult {
     let [
         // accounts in order (see `crate::instruction::EscrowInstruction` enum)
         escrow_state_info,
@@ -37,21 +107,53 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         vault,
         maker,
         taker,
+        rent_payer,
         payer,
-        token_program,
+        token_a_program,
+        token_b_program,
         associated_token_program,
         system_program,
+        fee_override_pair_info,
+        fee_override_token_a_info,
+        fee_override_token_b_info,
+        lamport_fee_vault,
+        remaining_accounts @ ..
     ] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
+    // Split the optional accounts flagged in the instruction data from the transfer hook accounts
+    let (fee_exemption_info, remaining_accounts) = match remaining_accounts {
+        [fee_exemption_info, remaining_accounts @ ..] if args.has_fee_exemption => {
+            (Some(fee_exemption_info), remaining_accounts)
+        }
+        _ if args.has_fee_exemption => return Err(ProgramError::NotEnoughAccountKeys),
+        _ => (None, remaining_accounts),
+    };
+    let (referrer_accounts, remaining_accounts) = match remaining_accounts {
+        [referrer, referrer_token_a_account, referrer_token_b_account, remaining_accounts @ ..]
+            if args.referrer.is_some() =>
+        {
+            (
+                Some((referrer, referrer_token_a_account, referrer_token_b_account)),
+                remaining_accounts,
+            )
+        }
+        _ if args.referrer.is_some() => return Err(ProgramError::NotEnoughAccountKeys),
+        _ => (None, remaining_accounts),
+    };
+
     // Ensure the taker signs the instruction
     if !taker.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
+    // Validate the token programs of the offered and wanted tokens
+    assert_is_token_program_of_mint(token_a_program, token_a_mint)?;
+    assert_is_token_program_of_mint(token_b_program, token_b_mint)?;
+
     // Deserialize the escrow state create program address
-    let escrow_state = EscrowState::try_from_slice(&escrow_state_info.data.borrow()[..])?;
+    let mut escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
     let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
 
     // Ensure the provided escrow state address is correct
@@ -59,25 +161,41 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         return Err(EscrowError::EscrowStateKeyMismatch.into());
     };
 
+    // Ensure taking offers is not paused
+    if escrow_state.is_take_paused {
+        return Err(EscrowError::TakePaused.into());
+    }
+
+    // Use the pending fees (if any) once in effect, the escrow state is left unchanged
+    escrow_state.apply_pending_fees(Clock::get()?.unix_timestamp);
+
     // Validate the escrow fee token accounts are owned by the escrow state (ATA)
     assert_is_associated_token_account(
         escrow_fee_token_a_account.key,
         escrow_state_info.key,
         token_a_mint.key,
+        token_a_program.key,
     )?;
     assert_is_associated_token_account(
         escrow_fee_token_b_account.key,
         escrow_state_info.key,
         token_b_mint.key,
+        token_b_program.key,
     )?;
 
     // Deserialize the offer
-    let offer = Offer::try_from_slice(&offer_info.data.borrow()[..])?;
+    let mut offer = load_program_account::<Offer>(program_id, offer_info)?;
 
     // Validate the offer
-    assert_eq!(&offer.maker, maker.key);
-    assert_eq!(&offer.token_a_mint, token_a_mint.key);
-    assert_eq!(&offer.token_b_mint, token_b_mint.key);
+    if offer.maker != *maker.key {
+        return Err(EscrowError::MakerKeyMismatch.into());
+    }
+    if offer.token_a_mint != *token_a_mint.key {
+        return Err(EscrowError::MintKeyMismatch.into());
+    }
+    if offer.token_b_mint != *token_b_mint.key {
+        return Err(EscrowError::MintKeyMismatch.into());
+    }
 
     let offer_address = Offer::create_program_address(program_id, maker.key, offer.id, offer.bump)?;
 
@@ -86,6 +204,47 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         return Err(EscrowError::OfferKeyMismatch.into());
     };
 
+    // Ensure the offer has not expired
+    if offer.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(EscrowError::OfferExpired.into());
+    }
+
+    // Ensure the taker is allowed to take the offer (if private or allowlisted)
+    if !offer.is_allowed_taker(taker.key, &args.taker_allowlist_proof) {
+        return Err(EscrowError::TakerKeyUnauthorized.into());
+    }
+
+    // Ensure the rent is sent back to the original funder of the offer
+    if *rent_payer.key != offer.rent_payer {
+        return Err(EscrowError::RentPayerKeyMismatch.into());
+    }
+
+    // Use the fee rates (and fee rounding, if recorded) when the offer was made, if the fee policy
+    // says so. Otherwise prefer the fee rates of a fee override for the mints (if any) over the
+    // programwide fees.
+    let (fee_rates, fee_rounding) = match (escrow_state.fee_policy, offer.fee_rates) {
+        (FeePolicy::AtCreation, Some(fee_rates)) => (
+            fee_rates,
+            offer.fee_rounding.unwrap_or(escrow_state.fee_rounding),
+        ),
+        _ => load_fee_rates(
+            program_id,
+            &escrow_state,
+            [
+                fee_override_pair_info,
+                fee_override_token_a_info,
+                fee_override_token_b_info,
+            ],
+            token_a_mint.key,
+            token_b_mint.key,
+        )?,
+    };
+
+    // Discount the maker fee as recorded when the offer was made, and the taker fee by the fee
+    // exemption of the taker (if provided)
+    let taker_fee_discount_bps = load_fee_discount(program_id, fee_exemption_info, taker.key)?;
+    let fee_rates = fee_rates.discounted(offer.maker_fee_discount_bps, taker_fee_discount_bps);
+
     let offer_signer_seed = &[
         Offer::SEED_PREFIX,
         maker.key.as_ref(),
@@ -94,68 +253,204 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     ];
 
     // Validate the receiving token B accout is owned by the maker (ATA)
-    assert_is_associated_token_account(maker_token_b_account.key, maker.key, token_b_mint.key)?;
+    assert_is_associated_token_account(
+        maker_token_b_account.key,
+        maker.key,
+        token_b_mint.key,
+        token_b_program.key,
+    )?;
 
     // Validate the receiving token A accout is owned by the taker (ATA)
-    assert_is_associated_token_account(taker_token_a_account.key, taker.key, token_a_mint.key)?;
-
-    // Create taker token A account if needed, before receiveing tokens
-    invoke(
-        &associated_token_account_instruction::create_associated_token_account_idempotent(
-            payer.key,
-            taker.key,
-            token_a_mint.key,
-            token_program.key,
-        ),
-        //   0. `[writeable,signer]` Funding account (must be a system account)
-        //   1. `[writeable]` Associated token account address to be created
-        //   2. `[]` Wallet address for the new associated token account
-        //   3. `[]` The token mint for the new associated token account
-        //   4. `[]` System program
-        //   5. `[]` SPL Token program
-        &[
-            payer.clone(),
-            taker_token_a_account.clone(),
-            taker.clone(),
-            token_a_mint.clone(),
-            system_program.clone(),
-            token_program.clone(),
-            associated_token_program.clone(),
-        ],
+    assert_is_associated_token_account(
+        taker_token_a_account.key,
+        taker.key,
+        token_a_mint.key,
+        token_a_program.key,
     )?;
 
-    // Create maker token B account if needed, before receiveing tokens
-    invoke(
-        &associated_token_account_instruction::create_associated_token_account_idempotent(
-            payer.key,
-            maker.key,
+    // Validate the referrer token accounts are owned by the referrer (ATA), if any
+    let is_referred = referrer_accounts.is_some();
+    if let Some((referrer, referrer_token_a_account, referrer_token_b_account)) = referrer_accounts
+    {
+        if args.referrer != Some(*referrer.key) {
+            return Err(EscrowError::ReferrerKeyMismatch.into());
+        }
+
+        // Ensure the taker or the maker does not refer the take to get a share of its own fees
+        if referrer.key == taker.key || referrer.key == maker.key {
+            return Err(EscrowError::ReferrerKeyUnauthorized.into());
+        }
+
+        assert_is_associated_token_account(
+            referrer_token_a_account.key,
+            referrer.key,
+            token_a_mint.key,
+            token_a_program.key,
+        )?;
+        assert_is_associated_token_account(
+            referrer_token_b_account.key,
+            referrer.key,
             token_b_mint.key,
-            token_program.key,
-        ),
-        //   0. `[writeable,signer]` Funding account (must be a system account)
-        //   1. `[writeable]` Associated token account address to be created
-        //   2. `[]` Wallet address for the new associated token account
-        //   3. `[]` The token mint for the new associated token account
-        //   4. `[]` System program
-        //   5. `[]` SPL Token program
-        &[
-            payer.clone(),
-            maker_token_b_account.clone(),
-            maker.clone(),
-            token_b_mint.clone(),
-            system_program.clone(),
-            token_program.clone(),
-            associated_token_program.clone(), // not required
-        ],
-    )?;
+            token_b_program.key,
+        )?;
+    }
+
+    // NOTE: Native SOL is wrapped from the taker's wallet, and unwrapped into the receiving wallet
+    // (token A in SOL is sent from the vault, leaving any token account of the taker untouched)
+    let is_token_a_native = is_native_mint(token_a_mint.key);
+    let is_token_b_native = is_native_mint(token_b_mint.key);
+
+    // Create taker token A account if needed, before receiveing tokens (received in SOL if native)
+    if !is_token_a_native {
+        invoke(
+            &associated_token_account_instruction::create_associated_token_account_idempotent(
+                payer.key,
+                taker.key,
+                token_a_mint.key,
+                token_a_program.key,
+            ),
+            //   0. `[writeable,signer]` Funding account (must be a system account)
+            //   1. `[writeable]` Associated token account address to be created
+            //   2. `[]` Wallet address for the new associated token account
+            //   3. `[]` The token mint for the new associated token account
+            //   4. `[]` System program
+            //   5. `[]` SPL Token program
+            &[
+                payer.clone(),
+                taker_token_a_account.clone(),
+                taker.clone(),
+                token_a_mint.clone(),
+                system_program.clone(),
+                token_a_program.clone(),
+                associated_token_program.clone(),
+            ],
+        )?;
+    }
+
+    // Create maker token B account if needed, before receiveing tokens (paid in SOL if native)
+    if !is_token_b_native {
+        invoke(
+            &associated_token_account_instruction::create_associated_token_account_idempotent(
+                payer.key,
+                maker.key,
+                token_b_mint.key,
+                token_b_program.key,
+            ),
+            //   0. `[writeable,signer]` Funding account (must be a system account)
+            //   1. `[writeable]` Associated token account address to be created
+            //   2. `[]` Wallet address for the new associated token account
+            //   3. `[]` The token mint for the new associated token account
+            //   4. `[]` System program
+            //   5. `[]` SPL Token program
+            &[
+                payer.clone(),
+                maker_token_b_account.clone(),
+                maker.clone(),
+                token_b_mint.clone(),
+                system_program.clone(),
+                token_b_program.clone(),
+                associated_token_program.clone(), // not required
+            ],
+        )?;
+    }
 
     // Read token amount in the offer's vault account
-    let vault_amount_a = TokenAccount::unpack(&vault.data.borrow())?.amount;
-    // let taker_amount_a_before_transfer =
-    //     TokenAccount::unpack(&taker_token_a_account.data.borrow())?.amount;
+    let vault_amount_a = unpack_token_account(vault)?.amount;
+
+    // Determine the amount of token A to take and the pro-rata amount of token B to pay
+    let token_a_amount = match token_a_amount {
+        Some(amount) if amount == 0 || amount > offer.token_a_offered_amount => {
+            return Err(EscrowError::InvalidTakeAmount.into());
+        }
+        Some(amount) => amount,
+        None => offer.token_a_offered_amount,
+    };
+    let is_final_fill = token_a_amount == offer.token_a_offered_amount;
+    let token_b_amount = if is_final_fill {
+        offer.token_b_wanted_amount
+    } else {
+        offer.get_token_b_amount(token_a_amount)?
+    };
+
+    // Drain the vault on the final fill, so that it can be closed
+    let token_a_amount = if is_final_fill {
+        vault_amount_a
+    } else {
+        token_a_amount
+    };
+
+    let token_a_decimals = get_mint_decimals(token_a_mint)?;
+    let token_b_decimals = get_mint_decimals(token_b_mint)?;
+
+    // NOTE: Escrow fees and maker proceeds are calculated on the amounts to be received,
+    // the taker covers Token-2022 transfer fees (if any) on top of them.
+
+    // Calculate token B fee amount (shared with the referrer, if any), and the amounts sent by
+    // the taker
+    let token_b_fee_amount = fee_rates.get_token_b_fee(token_b_amount, &fee_rounding)?;
+    let token_b_referral_fee_amount = if is_referred {
+        escrow_state.get_referral_fee(token_b_fee_amount)?
+    } else {
+        0
+    };
+    let token_b_escrow_fee_amount = token_b_fee_amount
+        .checked_sub(token_b_referral_fee_amount)
+        .ok_or(EscrowError::MathError)?;
+    let token_b_escrow_fee_amount_with_transfer_fee =
+        get_amount_with_transfer_fee(token_b_mint, token_b_escrow_fee_amount)?;
+    let token_b_referral_fee_amount_with_transfer_fee =
+        get_amount_with_transfer_fee(token_b_mint, token_b_referral_fee_amount)?;
+    let token_b_to_transfer_after_fee = token_b_amount
+        .checked_sub(token_b_fee_amount)
+        .ok_or(EscrowError::MathError)?;
+    let token_b_to_transfer_after_fee =
+        get_amount_with_transfer_fee(token_b_mint, token_b_to_transfer_after_fee)?;
+
+    // Calculate token A fee amount (shared with the referrer, if any), and the amounts sent from
+    // the vault
+    let token_a_fee_amount = fee_rates.get_token_a_fee(token_a_amount, &fee_rounding)?;
+    let token_a_referral_fee_amount = if is_referred {
+        escrow_state.get_referral_fee(token_a_fee_amount)?
+    } else {
+        0
+    };
+    let token_a_escrow_fee_amount = token_a_fee_amount
+        .checked_sub(token_a_referral_fee_amount)
+        .ok_or(EscrowError::MathError)?;
+    let token_a_escrow_fee_amount_with_transfer_fee =
+        get_amount_with_transfer_fee(token_a_mint, token_a_escrow_fee_amount)?;
+    let token_a_referral_fee_amount_with_transfer_fee =
+        get_amount_with_transfer_fee(token_a_mint, token_a_referral_fee_amount)?;
+    let token_a_to_transfer_after_fee = token_a_amount
+        .checked_sub(token_a_escrow_fee_amount_with_transfer_fee)
+        .and_then(|v| v.checked_sub(token_a_referral_fee_amount_with_transfer_fee))
+        .ok_or(EscrowError::MathError)?;
 
-    // Calculate token B fee amount
-    let token_b_fee_amount = escrow_state.get_token_b_fee(offer.token_b_wanted_amount)?;
+    // Ensure the taker does not pay more token B than expected
+    let token_b_to_pay = token_b_escrow_fee_amount_with_transfer_fee
+        .checked_add(token_b_referral_fee_amount_with_transfer_fee)
+        .and_then(|v| v.checked_add(token_b_to_transfer_after_fee))
+        .ok_or(EscrowError::MathError)?;
+    if args
+        .max_token_b_to_pay
+        .is_some_and(|max_token_b_to_pay| token_b_to_pay > max_token_b_to_pay)
+    {
+        return Err(EscrowError::SlippageExceeded.into());
+    }
+
+    // Ensure the taker does not receive less token A than expected
+    let token_a_to_receive = token_a_to_transfer_after_fee
+        .checked_sub(get_transfer_fee(
+            token_a_mint,
+            token_a_to_transfer_after_fee,
+        )?)
+        .ok_or(EscrowError::MathError)?;
+    if args
+        .min_token_a_to_receive
+        .is_some_and(|min_token_a_to_receive| token_a_to_receive < min_token_a_to_receive)
+    {
+        return Err(EscrowError::SlippageExceeded.into());
+    }
 
     // Create escrow fee token B account (escrow state ATA) if needed, before receiveing tokens for fee
     invoke(
@@ -163,7 +458,7 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
             payer.key,
             &escrow_state_address,
             token_b_mint.key,
-            token_program.key,
+            token_b_program.key,
         ),
         //   0. `[writeable,signer]` Funding account (must be a system account)
         //   1. `[writeable]` Associated token account address to be created
@@ -177,59 +472,108 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
             escrow_state_info.clone(),
             token_b_mint.clone(),
             system_program.clone(),
-            token_program.clone(),
+            token_b_program.clone(),
             associated_token_program.clone(),
         ],
     )?;
 
-    // Transfer fees for token B from taker to escrow fee account for token B
-    invoke(
-        &token_instruction::transfer(
-            token_program.key,
-            taker_token_b_account.key,
-            escrow_fee_token_b_account.key,
-            taker.key,
-            &[taker.key],
-            token_b_fee_amount,
-        )?,
-        //   0. `[writable]` The source account.
-        //   1. `[writable]` The destination account.
-        //   2. `[signer]` The source account's owner/delegate.
-        &[
+    if is_token_b_native {
+        // Wrap fees for token B from taker to escrow fee account for token B
+        wrap_native(
+            token_b_program,
+            taker,
+            escrow_fee_token_b_account,
+            system_program,
+            token_b_escrow_fee_amount_with_transfer_fee,
+        )?;
+
+        // Transfer SOL from taker to maker
+        invoke(
+            &system_instruction::transfer(taker.key, maker.key, token_b_to_transfer_after_fee),
+            //   0. `[WRITE, SIGNER]` Funding account
+            //   1. `[WRITE]` Recipient account
+            &[taker.clone(), maker.clone(), system_program.clone()],
+        )?;
+    } else {
+        // Transfer fees for token B from taker to escrow fee account for token B
+        invoke_transfer_checked(
+            token_b_program.key,
             taker_token_b_account.clone(),
+            token_b_mint.clone(),
             escrow_fee_token_b_account.clone(),
             taker.clone(),
-            token_program.clone(),
-        ],
-    )?;
+            remaining_accounts,
+            token_b_escrow_fee_amount_with_transfer_fee,
+            token_b_decimals,
+            &[],
+        )?;
 
-    // Transfer token B from taker (TA) to maker (ATA)
-    let token_b_to_transfer_after_fee = offer
-        .token_b_wanted_amount
-        .checked_sub(token_b_fee_amount)
-        .ok_or(EscrowError::MathError)?;
-    invoke(
-        &token_instruction::transfer(
-            token_program.key,
-            taker_token_b_account.key,
-            maker_token_b_account.key,
-            taker.key,
-            &[taker.key],
-            token_b_to_transfer_after_fee,
-        )?,
-        //   0. `[writable]` The source account.
-        //   1. `[writable]` The destination account.
-        //   2. `[signer]` The source account's owner/delegate.
-        &[
+        // Transfer token B from taker (TA) to maker (ATA)
+        invoke_transfer_checked(
+            token_b_program.key,
             taker_token_b_account.clone(),
+            token_b_mint.clone(),
             maker_token_b_account.clone(),
             taker.clone(),
-            token_program.clone(),
-        ],
-    )?;
+            remaining_accounts,
+            token_b_to_transfer_after_fee,
+            token_b_decimals,
+            &[],
+        )?;
+    }
 
-    // Calculate token A fee amount
-    let token_a_fee_amount = escrow_state.get_token_a_fee(vault_amount_a)?;
+    if let Some((referrer, _, referrer_token_b_account)) =
+        referrer_accounts.filter(|_| token_b_referral_fee_amount_with_transfer_fee != 0)
+    {
+        // Create referrer token B account (ATA) if needed, before receiveing tokens for fee
+        invoke(
+            &associated_token_account_instruction::create_associated_token_account_idempotent(
+                payer.key,
+                referrer.key,
+                token_b_mint.key,
+                token_b_program.key,
+            ),
+            //   0. `[writeable,signer]` Funding account (must be a system account)
+            //   1. `[writeable]` Associated token account address to be created
+            //   2. `[]` Wallet address for the new associated token account
+            //   3. `[]` The token mint for the new associated token account
+            //   4. `[]` System program
+            //   5. `[]` SPL Token program
+            &[
+                payer.clone(),
+                referrer_token_b_account.clone(),
+                referrer.clone(),
+                token_b_mint.clone(),
+                system_program.clone(),
+                token_b_program.clone(),
+                associated_token_program.clone(),
+            ],
+        )?;
+
+        if is_token_b_native {
+            // Wrap the referral share of fees for token B from taker to referrer
+            wrap_native(
+                token_b_program,
+                taker,
+                referrer_token_b_account,
+                system_program,
+                token_b_referral_fee_amount_with_transfer_fee,
+            )?;
+        } else {
+            // Transfer the referral share of fees for token B from taker to referrer
+            invoke_transfer_checked(
+                token_b_program.key,
+                taker_token_b_account.clone(),
+                token_b_mint.clone(),
+                referrer_token_b_account.clone(),
+                taker.clone(),
+                remaining_accounts,
+                token_b_referral_fee_amount_with_transfer_fee,
+                token_b_decimals,
+                &[],
+            )?;
+        }
+    }
 
     // Create escrow fee token A account (escrow state ATA) if needed, before receiveing tokens for fee
     invoke(
@@ -237,7 +581,7 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
             payer.key,
             &escrow_state_address,
             token_a_mint.key,
-            token_program.key,
+            token_a_program.key,
         ),
         //   0. `[writeable,signer]` Funding account (must be a system account)
         //   1. `[writeable]` Associated token account address to be created
@@ -251,96 +595,193 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
             escrow_state_info.clone(),
             token_a_mint.clone(),
             system_program.clone(),
-            token_program.clone(),
+            token_a_program.clone(),
             associated_token_program.clone(),
         ],
     )?;
 
     // Transfer fees for token A from vault to escrow fee account for token A
-    invoke_signed(
-        &token_instruction::transfer(
-            token_program.key,
-            vault.key,
-            escrow_fee_token_a_account.key,
-            offer_info.key,
-            &[],
-            token_a_fee_amount,
-        )?,
-        //   0. `[writable]` The source account.
-        //   1. `[writable]` The destination account.
-        //   2. `[signer]` The source account's owner/delegate.
-        &[
-            vault.clone(),
-            escrow_fee_token_a_account.clone(),
-            offer_info.clone(),
-            token_program.clone(),
-        ],
+    invoke_transfer_checked(
+        token_a_program.key,
+        vault.clone(),
+        token_a_mint.clone(),
+        escrow_fee_token_a_account.clone(),
+        offer_info.clone(),
+        remaining_accounts,
+        token_a_escrow_fee_amount_with_transfer_fee,
+        token_a_decimals,
         &[offer_signer_seed],
     )?;
 
-    // Transfer token A from vault (Offer ATA) to taker (ATA)
-    let token_a_to_transfer_after_fee = vault_amount_a
-        .checked_sub(token_a_fee_amount)
-        .ok_or(EscrowError::MathError)?;
-    invoke_signed(
-        &token_instruction::transfer(
-            token_program.key,
-            vault.key,
-            taker_token_a_account.key,
-            offer_info.key,
-            &[],
-            token_a_to_transfer_after_fee,
-        )?,
-        //   0. `[writable]` The source account.
-        //   1. `[writable]` The destination account.
-        //   2. `[signer]` The source account's owner/delegate.
-        &[
+    if let Some((referrer, referrer_token_a_account, _)) =
+        referrer_accounts.filter(|_| token_a_referral_fee_amount_with_transfer_fee != 0)
+    {
+        // Create referrer token A account (ATA) if needed, before receiveing tokens for fee
+        invoke(
+            &associated_token_account_instruction::create_associated_token_account_idempotent(
+                payer.key,
+                referrer.key,
+                token_a_mint.key,
+                token_a_program.key,
+            ),
+            //   0. `[writeable,signer]` Funding account (must be a system account)
+            //   1. `[writeable]` Associated token account address to be created
+            //   2. `[]` Wallet address for the new associated token account
+            //   3. `[]` The token mint for the new associated token account
+            //   4. `[]` System program
+            //   5. `[]` SPL Token program
+            &[
+                payer.clone(),
+                referrer_token_a_account.clone(),
+                referrer.clone(),
+                token_a_mint.clone(),
+                system_program.clone(),
+                token_a_program.clone(),
+                associated_token_program.clone(),
+            ],
+        )?;
+
+        // Transfer the referral share of fees for token A from vault to referrer
+        invoke_transfer_checked(
+            token_a_program.key,
             vault.clone(),
-            taker_token_a_account.clone(),
+            token_a_mint.clone(),
+            referrer_token_a_account.clone(),
             offer_info.clone(),
-            token_program.clone(), // not required
-        ],
-        &[offer_signer_seed],
+            remaining_accounts,
+            token_a_referral_fee_amount_with_transfer_fee,
+            token_a_decimals,
+            &[offer_signer_seed],
+        )?;
+    }
+
+    // Pay the flat lamport fee of taking an offer (if any) from the funding account
+    pay_lamport_fee(
+        program_id,
+        lamport_fee_vault,
+        payer,
+        system_program,
+        escrow_state.take_lamport_fee,
     )?;
 
-    // let taker_amount_a = TokenAccount::unpack(&taker_token_a_account.data.borrow())?.amount;
-    // let maker_amount_b = TokenAccount::unpack(&maker_token_b_account.data.borrow())?.amount;
-    // let escrow_fee_amount_a =
-    //     TokenAccount::unpack(&escrow_fee_token_a_account.data.borrow())?.amount;
-    // let escrow_fee_amount_b =
-    //     TokenAccount::unpack(&escrow_fee_token_b_account.data.borrow())?.amount;
-
-    // assert_eq!(
-    //     taker_amount_a,
-    //     taker_amount_a_before_transfer + vault_amount_a - escrow_fee_amount_a
-    // );
-    // assert_eq!(
-    //     maker_amount_b,
-    //     taker_amount_a_before_transfer + offer.token_b_wanted_amount - escrow_fee_amount_b
-    // );
-
-    // Close the vault account
-    invoke_signed(
-        &token_instruction::close_account(
-            token_program.key,
-            vault.key,
-            payer.key,
-            offer_info.key,
-            &[],
-        )?,
-        &[
+    // NOTE: Native SOL is sent from the vault by lamports, moved directly only after the CPIs (which
+    // require the lamports of their accounts to balance)
+    if is_token_a_native {
+        let vault_lamports = vault.lamports();
+        let vault_amount_a = unpack_token_account(vault)?.amount;
+
+        // Unwrap SOL in vault into the offer account, closing the vault
+        unwrap_native(token_a_program, vault, offer_info, &[offer_signer_seed])?;
+
+        // Recreate the vault with the rest of SOL until the offer is fully filled, funded by the
+        // funding account and refunded from the lamports of the closed vault
+        let lamports_to_payer = if is_final_fill {
+            0
+        } else {
+            invoke(
+                &associated_token_account_instruction::create_associated_token_account_idempotent(
+                    payer.key,
+                    offer_info.key,
+                    token_a_mint.key,
+                    token_a_program.key,
+                ),
+                //   0. `[writeable,signer]` Funding account (must be a system account)
+                //   1. `[writeable]` Associated token account address to be created
+                //   2. `[]` Wallet address for the new associated token account
+                //   3. `[]` The token mint for the new associated token account
+                //   4. `[]` System program
+                //   5. `[]` SPL Token program
+                &[
+                    payer.clone(),
+                    vault.clone(),
+                    offer_info.clone(),
+                    token_a_mint.clone(),
+                    system_program.clone(),
+                    token_a_program.clone(),
+                    associated_token_program.clone(),
+                ],
+            )?;
+
+            let vault_amount_a_after_transfer = vault_amount_a
+                .checked_sub(token_a_to_transfer_after_fee)
+                .ok_or(EscrowError::MathError)?;
+            wrap_native(
+                token_a_program,
+                payer,
+                vault,
+                system_program,
+                vault_amount_a_after_transfer,
+            )?;
+
+            vault_lamports
+                .checked_sub(token_a_to_transfer_after_fee)
+                .ok_or(EscrowError::MathError)?
+        };
+
+        // Send SOL to taker (and refund the funding account), the rest of the vault lamports
+        // being rent sent back to the original funder on the final fill
+        **offer_info.lamports.borrow_mut() -= token_a_to_transfer_after_fee + lamports_to_payer;
+        **taker.lamports.borrow_mut() += token_a_to_transfer_after_fee;
+        **payer.lamports.borrow_mut() += lamports_to_payer;
+    } else {
+        // Transfer token A from vault (Offer ATA) to taker (ATA)
+        invoke_transfer_checked(
+            token_a_program.key,
             vault.clone(),
-            payer.clone(),
+            token_a_mint.clone(),
+            taker_token_a_account.clone(),
             offer_info.clone(),
-            token_program.clone(),
-        ],
-        &[offer_signer_seed],
-    )?;
+            remaining_accounts,
+            token_a_to_transfer_after_fee,
+            token_a_decimals,
+            &[offer_signer_seed],
+        )?;
+    }
+
+    // Keep the offer alive with the remaining amount until it is fully filled
+    if !is_final_fill {
+        offer.token_a_offered_amount = offer
+            .token_a_offered_amount
+            .checked_sub(token_a_amount)
+            .ok_or(EscrowError::MathError)?;
+        offer.token_b_wanted_amount = offer
+            .token_b_wanted_amount
+            .checked_sub(token_b_amount)
+            .ok_or(EscrowError::MathError)?;
+
+        // Write data into offer account
+        save_program_account(offer_info, &offer)?;
+
+        return Ok(());
+    }
+
+    // Close the vault account (already closed when unwrapping SOL)
+    if !is_token_a_native {
+        // Harvest withheld Token-2022 transfer fees (if any), before closing the vault
+        harvest_withheld_transfer_fees(token_a_program, token_a_mint, vault)?;
+
+        invoke_signed(
+            &token_instruction::close_account(
+                token_a_program.key,
+                vault.key,
+                rent_payer.key,
+                offer_info.key,
+                &[],
+            )?,
+            &[
+                vault.clone(),
+                rent_payer.clone(),
+                offer_info.clone(),
+                token_a_program.clone(),
+            ],
+            &[offer_signer_seed],
+        )?;
+    }
 
-    // Send the rent back to the payer
+    // Send the rent back to the original funder
     let lamports = offer_info.lamports();
     **offer_info.lamports.borrow_mut() -= lamports;
-    **payer.lamports.borrow_mut() += lamports;
+    **rent_payer.lamports.borrow_mut() += lamports;
 
     // Realloc the account to zero
     offer_info.realloc(0, true)?;
@@ -0,0 +1,76 @@
+//! Instruction for manager to update the fee rates of a fee override.
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+use crate::{
+    errors::EscrowError,
+    state::{EscrowState, FeeOverride},
+    utils::{assert_is_bps_in_range, load_program_account, save_program_account},
+};
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct UpdateFeeOverrideArgs {
+    pub maker_fee_bps: u16,
+    pub taker_fee_bps: u16,
+}
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: UpdateFeeOverrideArgs,
+) -> ProgramResult {
+    // Check the range of bps values in args
+    assert_is_bps_in_range(args.maker_fee_bps)?;
+    assert_is_bps_in_range(args.taker_fee_bps)?;
+
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        escrow_state_info,
+        fee_override_info,
+        manager,
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the manager signs the instruction
+    if !manager.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Deserialize the escrow state and create program address
+    let escrow_state = load_program_account::<EscrowState>(program_id, escrow_state_info)?;
+    let escrow_state_address = EscrowState::create_program_address(program_id, escrow_state.bump)?;
+
+    // Ensure the provided escrow state address is correct
+    if *escrow_state_info.key != escrow_state_address {
+        return Err(EscrowError::EscrowStateKeyMismatch.into());
+    };
+
+    // Ensure the provided manager is authorized
+    if *manager.key != escrow_state.manager {
+        return Err(EscrowError::ManagerKeyUnauthorized.into());
+    }
+
+    // Deserialize the fee override
+    let mut fee_override = load_program_account::<FeeOverride>(program_id, fee_override_info)?;
+
+    // Ensure the provided fee override address is correct
+    if *fee_override_info.key != fee_override.create_program_address(program_id)? {
+        return Err(EscrowError::FeeOverrideKeyMismatch.into());
+    };
+
+    fee_override.maker_fee_bps = args.maker_fee_bps;
+    fee_override.taker_fee_bps = args.taker_fee_bps;
+
+    // Write data into fee override account
+    save_program_account(fee_override_info, &fee_override)?;
+
+    solana_program::msg!("Updated fee override: {:?}", fee_override);
+
+    Ok(())
+}
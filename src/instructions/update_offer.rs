@@ -0,0 +1,160 @@
+//! Instruction for maker to update an existing offer in place.
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+    spl_token_2022::onchain::invoke_transfer_checked,
+};
+
+use crate::{
+    errors::EscrowError,
+    state::Offer,
+    utils::{
+        assert_is_associated_token_account, assert_is_token_program_of_mint,
+        assert_token_account_mint_and_owner, get_mint_decimals, get_transfer_fee,
+        load_program_account, save_program_account, unpack_token_account,
+    },
+};
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct UpdateOfferArgs {
+    /// New amount of token B wanted for the whole remaining token A, unchanged if `None`
+    pub token_b_wanted_amount: Option<u64>,
+    /// Amount of token A to deposit into the vault, before any Token-2022 transfer fee
+    pub token_a_deposit_amount: u64,
+    /// Amount of token A to withdraw from the vault, must be lower than the remaining offer
+    pub token_a_withdraw_amount: u64,
+}
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    args: UpdateOfferArgs,
+) -> ProgramResult {
+    let [
+        // accounts in order (see `crate::instruction::EscrowInstruction` enum)
+        offer_info,
+        token_a_mint,
+        maker_token_a_account,
+        vault,
+        maker,
+        token_program,
+        remaining_accounts @ ..
+    ] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Ensure the maker signs the instruction
+    if !maker.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Validate the token program of the offered token
+    assert_is_token_program_of_mint(token_program, token_a_mint)?;
+
+    // Deserialize the offer
+    let mut offer = load_program_account::<Offer>(program_id, offer_info)?;
+
+    // Validate the offer
+    if offer.maker != *maker.key {
+        return Err(EscrowError::MakerKeyMismatch.into());
+    }
+    if offer.token_a_mint != *token_a_mint.key {
+        return Err(EscrowError::MintKeyMismatch.into());
+    }
+
+    // Create program address of the offer
+    let offer_address = Offer::create_program_address(program_id, maker.key, offer.id, offer.bump)?;
+
+    // Ensure the provided offer address is correct
+    if *offer_info.key != offer_address {
+        return Err(EscrowError::OfferKeyMismatch.into());
+    };
+
+    let offer_signer_seed = &[
+        Offer::SEED_PREFIX,
+        maker.key.as_ref(),
+        &offer.id.to_le_bytes(),
+        &[offer.bump],
+    ];
+
+    // Validate the owner and mint of the maker's token A account
+    assert_token_account_mint_and_owner(maker_token_a_account, maker.key, token_a_mint.key)?;
+
+    // Validate vault is owned by the offer account (ATA)
+    assert_is_associated_token_account(
+        vault.key,
+        offer_info.key,
+        token_a_mint.key,
+        token_program.key,
+    )?;
+
+    // Calculate token A amount to be escrowed after the Token-2022 transfer fee (if any)
+    let token_a_escrowed_amount = args
+        .token_a_deposit_amount
+        .checked_sub(get_transfer_fee(token_a_mint, args.token_a_deposit_amount)?)
+        .ok_or(EscrowError::MathError)?;
+
+    // Calculate the remaining offer, ensuring it is not emptied (use `CancelOffer` instead)
+    let token_a_remaining_amount = offer
+        .token_a_offered_amount
+        .checked_add(token_a_escrowed_amount)
+        .ok_or(EscrowError::MathError)?
+        .checked_sub(args.token_a_withdraw_amount)
+        .filter(|token_a_remaining_amount| *token_a_remaining_amount > 0)
+        .ok_or(EscrowError::InvalidWithdrawAmount)?;
+
+    let token_a_decimals = get_mint_decimals(token_a_mint)?;
+    let vault_token_amount_before = unpack_token_account(vault)?.amount;
+
+    if args.token_a_deposit_amount > 0 {
+        // Transfer token A from maker to vault
+        invoke_transfer_checked(
+            token_program.key,
+            maker_token_a_account.clone(),
+            token_a_mint.clone(),
+            vault.clone(),
+            maker.clone(),
+            remaining_accounts,
+            args.token_a_deposit_amount,
+            token_a_decimals,
+            &[],
+        )?;
+    }
+
+    if args.token_a_withdraw_amount > 0 {
+        // Transfer token A from vault to maker
+        invoke_transfer_checked(
+            token_program.key,
+            vault.clone(),
+            token_a_mint.clone(),
+            maker_token_a_account.clone(),
+            offer_info.clone(),
+            remaining_accounts,
+            args.token_a_withdraw_amount,
+            token_a_decimals,
+            &[offer_signer_seed],
+        )?;
+    }
+
+    // Ensure the vault balance changed by exactly the escrowed and withdrawn amounts
+    let vault_token_amount = unpack_token_account(vault)?.amount;
+    if vault_token_amount.checked_add(args.token_a_withdraw_amount)
+        != vault_token_amount_before.checked_add(token_a_escrowed_amount)
+    {
+        return Err(EscrowError::VaultBalanceMismatch.into());
+    }
+
+    offer.token_a_offered_amount = token_a_remaining_amount;
+
+    if let Some(token_b_wanted_amount) = args.token_b_wanted_amount {
+        offer.token_b_wanted_amount = token_b_wanted_amount;
+    }
+
+    // Write updated data into offer account
+    save_program_account(offer_info, &offer)?;
+
+    Ok(())
+}
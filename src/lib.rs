@@ -5,6 +5,7 @@ declare_id!("DNkSkUvP9fYkRJi9HV4B584xdNxwPBrmzubThtkU6yA3");
 pub mod consts;
 pub mod errors;
 pub mod instructions;
+pub mod merkle;
 pub mod processor;
 pub mod state;
 mod utils;
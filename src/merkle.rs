@@ -0,0 +1,119 @@
+//! Merkle tree of wallet addresses, used for offer taker allowlists.
+//!
+//! Leaves and nodes are domain separated, and node children are sorted before hashing,
+//! so that proofs don't need to encode the position of each sibling.
+use solana_program::{hash::hashv, pubkey::Pubkey};
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+pub fn hash_leaf(pubkey: &Pubkey) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, pubkey.as_ref()]).to_bytes()
+}
+
+fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if left <= right {
+        (left, right)
+    } else {
+        (right, left)
+    };
+
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Verify the given pubkey is part of the Merkle tree with the given root.
+pub fn verify_proof(root: &[u8; 32], proof: &[[u8; 32]], pubkey: &Pubkey) -> bool {
+    let computed_root = proof
+        .iter()
+        .fold(hash_leaf(pubkey), |node, sibling| hash_node(&node, sibling));
+
+    computed_root == *root
+}
+
+/// Hash each level of the tree built from the given pubkeys, from the leaves up to the root.
+///
+/// A node without a sibling is carried up to the next level as is.
+#[cfg(not(target_os = "solana"))]
+fn get_levels(pubkeys: &[Pubkey]) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![pubkeys.iter().map(hash_leaf).collect::<Vec<_>>()];
+
+    while levels.last().is_some_and(|level| level.len() > 1) {
+        let level = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_node(left, right),
+                [node] => *node,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(level);
+    }
+
+    levels
+}
+
+/// Build the Merkle root of the given pubkeys (off-chain helper).
+///
+/// Returns `None` if no pubkeys are given.
+#[cfg(not(target_os = "solana"))]
+pub fn get_root(pubkeys: &[Pubkey]) -> Option<[u8; 32]> {
+    get_levels(pubkeys).last()?.first().copied()
+}
+
+/// Build the Merkle proof of the given pubkey, within the tree of the given pubkeys
+/// (off-chain helper).
+///
+/// Returns `None` if the pubkey is not part of the given pubkeys.
+#[cfg(not(target_os = "solana"))]
+pub fn get_proof(pubkeys: &[Pubkey], pubkey: &Pubkey) -> Option<Vec<[u8; 32]>> {
+    let mut index = pubkeys.iter().position(|p| p == pubkey)?;
+    let levels = get_levels(pubkeys);
+
+    let mut proof = vec![];
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+
+    Some(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_verifies_proofs_against_root() {
+        for count in 1..=9 {
+            let pubkeys = (0..count).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+            let root = get_root(&pubkeys).unwrap();
+
+            for pubkey in &pubkeys {
+                let proof = get_proof(&pubkeys, pubkey).unwrap();
+                assert!(verify_proof(&root, &proof, pubkey));
+            }
+        }
+    }
+
+    #[test]
+    fn it_fails_to_verify_proofs_for_other_pubkeys() {
+        let pubkeys = (0..5).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let root = get_root(&pubkeys).unwrap();
+        let other_pubkey = Pubkey::new_unique();
+
+        assert!(get_proof(&pubkeys, &other_pubkey).is_none());
+
+        let proof = get_proof(&pubkeys, &pubkeys[0]).unwrap();
+        assert!(!verify_proof(&root, &proof, &other_pubkey));
+        assert!(!verify_proof(&root, &[], &other_pubkey));
+    }
+
+    #[test]
+    fn it_has_no_root_for_empty_tree() {
+        assert!(get_root(&[]).is_none());
+    }
+}
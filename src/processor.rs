@@ -21,7 +21,52 @@ pub fn process_instruction(
         EscrowInstruction::SetManager => set_manager::process(program_id, accounts),
         EscrowInstruction::CollectFee(args) => collect_fee::process(program_id, accounts, args),
         EscrowInstruction::MakeOffer(args) => make_offer::process(program_id, accounts, args),
-        EscrowInstruction::TakeOffer => take_offer::process(program_id, accounts),
+        EscrowInstruction::TakeOffer(args) => take_offer::process(program_id, accounts, args),
         EscrowInstruction::CancelOffer => cancel_offer::process(program_id, accounts),
+        EscrowInstruction::PartialTakeOffer(args) => {
+            take_offer::process_partial(program_id, accounts, args)
+        }
+        EscrowInstruction::ExpireOffer => expire_offer::process(program_id, accounts),
+        EscrowInstruction::UpdateOffer(args) => update_offer::process(program_id, accounts, args),
+        EscrowInstruction::MigrateState => migrate_state::process(program_id, accounts),
+        EscrowInstruction::MigrateOffer => migrate_offer::process(program_id, accounts),
+        EscrowInstruction::AcceptManager => accept_manager::process(program_id, accounts),
+        EscrowInstruction::CancelManagerNomination => {
+            cancel_manager_nomination::process(program_id, accounts)
+        }
+        EscrowInstruction::SetPause(args) => set_pause::process(program_id, accounts, args),
+        EscrowInstruction::CreateFeeOverride(args) => {
+            create_fee_override::process(program_id, accounts, args)
+        }
+        EscrowInstruction::UpdateFeeOverride(args) => {
+            update_fee_override::process(program_id, accounts, args)
+        }
+        EscrowInstruction::CloseFeeOverride => close_fee_override::process(program_id, accounts),
+        EscrowInstruction::SetFeePolicy(args) => {
+            set_fee_policy::process(program_id, accounts, args)
+        }
+        EscrowInstruction::SetFeeChangeDelay(args) => {
+            set_fee_change_delay::process(program_id, accounts, args)
+        }
+        EscrowInstruction::CancelPendingFees => cancel_pending_fees::process(program_id, accounts),
+        EscrowInstruction::SetFeeCollector(args) => {
+            set_fee_collector::process(program_id, accounts, args)
+        }
+        EscrowInstruction::SetReferralShare(args) => {
+            set_referral_share::process(program_id, accounts, args)
+        }
+        EscrowInstruction::AddFeeExemption(args) => {
+            add_fee_exemption::process(program_id, accounts, args)
+        }
+        EscrowInstruction::RemoveFeeExemption => {
+            remove_fee_exemption::process(program_id, accounts)
+        }
+        EscrowInstruction::SetLamportFees(args) => {
+            set_lamport_fees::process(program_id, accounts, args)
+        }
+        EscrowInstruction::CollectLamportFees => {
+            collect_lamport_fees::process(program_id, accounts)
+        }
+        EscrowInstruction::CollectFees(args) => collect_fees::process(program_id, accounts, args),
     }
 }
@@ -1,7 +1,162 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::{Pubkey, PubkeyError};
 
-use crate::{consts::MAX_BPS_VALUE, errors::EscrowError};
+use crate::{consts::MAX_BPS_VALUE, errors::EscrowError, merkle};
+
+/// Type of a program-owned account, stored as the first byte of its data.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountType {
+    Uninitialized,
+    EscrowState,
+    Offer,
+    FeeOverride,
+    FeeExemption,
+    LamportFeeVault,
+}
+
+/// Program-owned account, tagged with its `AccountType` and layout version
+/// (see `crate::utils::load_program_account`).
+///
+/// Accounts are allocated with `LEN` bytes. The data past the serialized fields is reserved
+/// and kept zeroed, so that fields appended in later versions decode from zero bytes.
+pub trait ProgramAccount: BorshDeserialize + BorshSerialize {
+    const ACCOUNT_TYPE: AccountType;
+    const VERSION: u8;
+    const LEN: usize;
+}
+
+/// Maker and taker fee rates, from the escrow state or a fee override.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeRates {
+    pub maker_fee_bps: u16,
+    pub taker_fee_bps: u16,
+}
+
+impl FeeRates {
+    /// Calculate token A (offer token) fee amount.
+    ///
+    /// The fee is to be levied **from the amount transferred from vault to taker**.
+    pub fn get_token_a_fee(
+        &self,
+        amount: u64,
+        fee_rounding: &FeeRounding,
+    ) -> Result<u64, EscrowError> {
+        fee_rounding.get_fee(amount, self.taker_fee_bps, fee_rounding.min_taker_fee)
+    }
+
+    /// Calculate token B (ask token) fee amount.
+    ///
+    /// The fee is to be levied **from the amount transferred from taker to maker**.
+    pub fn get_token_b_fee(
+        &self,
+        amount: u64,
+        fee_rounding: &FeeRounding,
+    ) -> Result<u64, EscrowError> {
+        fee_rounding.get_fee(amount, self.maker_fee_bps, fee_rounding.min_maker_fee)
+    }
+
+    /// Fee rates reduced by the given discounts of the maker and taker fees (see
+    /// `FeeExemption`), in basis points of the rates.
+    pub fn discounted(self, maker_discount_bps: u16, taker_discount_bps: u16) -> Self {
+        let discount = |fee_bps: u16, discount_bps: u16| {
+            let fee_bps = u32::from(fee_bps)
+                * u32::from(MAX_BPS_VALUE.saturating_sub(discount_bps))
+                / u32::from(MAX_BPS_VALUE);
+            // Never more than the undiscounted rate, so always in range
+            fee_bps as u16
+        };
+
+        Self {
+            maker_fee_bps: discount(self.maker_fee_bps, maker_discount_bps),
+            taker_fee_bps: discount(self.taker_fee_bps, taker_discount_bps),
+        }
+    }
+}
+
+/// Rounding of fee amounts to token units.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoundingMode {
+    #[default]
+    Floor,
+    Ceil,
+    /// Rounded to the nearest unit, halves rounded up
+    HalfUp,
+}
+
+impl RoundingMode {
+    fn div(self, numerator: u128, denominator: u128) -> Option<u128> {
+        let rounding = match self {
+            Self::Floor => 0,
+            Self::Ceil => denominator.checked_sub(1)?,
+            Self::HalfUp => denominator.checked_div(2)?,
+        };
+
+        numerator.checked_add(rounding)?.checked_div(denominator)
+    }
+}
+
+/// How fee amounts are rounded, and the minimum fee amount levied on each side of a take.
+///
+/// NOTE: Minimum fees are in base units of whichever token the fee is levied in, regardless of
+/// the decimals of its mint (a minimum of 1 is a whole token for a mint without decimals).
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeRounding {
+    pub mode: RoundingMode,
+    /// Minimum token B fee amount in base units, unless the maker fee rate is zero
+    pub min_maker_fee: Option<u64>,
+    /// Minimum token A fee amount in base units, unless the taker fee rate is zero
+    pub min_taker_fee: Option<u64>,
+}
+
+impl FeeRounding {
+    /// Calculate the fee amount at the given rate, raised to the minimum fee (if any) for
+    /// nonzero rates. The fee never exceeds the amount.
+    pub fn get_fee(
+        &self,
+        amount: u64,
+        fee_bps: u16,
+        min_fee: Option<u64>,
+    ) -> Result<u64, EscrowError> {
+        let fee = u128::from(amount)
+            .checked_mul(u128::from(fee_bps))
+            .and_then(|v| self.mode.div(v, u128::from(MAX_BPS_VALUE)))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(EscrowError::MathError)?;
+
+        let fee = match min_fee {
+            Some(min_fee) if fee_bps != 0 => fee.max(min_fee),
+            _ => fee,
+        };
+
+        Ok(fee.min(amount))
+    }
+}
+
+/// Fee rates announced by the manager, in effect from a later time (see `EscrowState`).
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingFees {
+    pub fee_rates: FeeRates,
+    /// Unix timestamp from which the fee rates are in effect
+    pub effective_at: i64,
+    pub fee_rounding: FeeRounding,
+}
+
+/// Fee change delay decreased by the manager, in effect from a later time (see `EscrowState`).
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingFeeChangeDelay {
+    pub fee_change_delay: u32,
+    /// Unix timestamp from which the fee change delay is in effect
+    pub effective_at: i64,
+}
+
+/// When the fee rates levied on taking an offer are determined.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeePolicy {
+    /// Fee rates in effect when the offer is taken
+    AtTake,
+    /// Fee rates in effect when the offer was made (see `Offer::fee_rates`)
+    AtCreation,
+}
 
 /// Singleton program state that describes the manager authority and escrow fees.
 ///
@@ -10,10 +165,47 @@ use crate::{consts::MAX_BPS_VALUE, errors::EscrowError};
 /// PDA seed format: [b"state"]
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
 pub struct EscrowState {
+    pub account_type: AccountType,
+    pub version: u8,
     pub manager: Pubkey,
     pub maker_fee_bps: u16,
     pub taker_fee_bps: u16,
     pub bump: u8,
+    /// Manager nominated by the current manager, until it accepts the authority
+    pub pending_manager: Option<Pubkey>,
+    /// Whether making new offers is paused
+    pub is_make_paused: bool,
+    /// Whether taking offers is paused (canceling offers is always allowed)
+    pub is_take_paused: bool,
+    /// Whether offers are taken at the fee rates of their creation or of their take
+    pub fee_policy: FeePolicy,
+    /// Fee rates announced with `SetFees`, replacing the fee rates above once in effect
+    pub pending_fees: Option<PendingFees>,
+    /// Minimum delay in seconds between announcing fee rates and their effect (programwide fee
+    /// rates only, see `SetFeeChangeDelay`)
+    pub fee_change_delay: u32,
+    /// Authority allowed to collect fees besides the manager
+    pub fee_collector: Option<Pubkey>,
+    /// Owner of the only token accounts (ATA) fees can be collected to, any if `None`
+    pub fee_destination: Option<Pubkey>,
+    /// Share of the fees sent to the referrer of a take (if any), in basis points
+    pub referral_share_bps: u16,
+    /// Rounding and minimum of the fee amounts, at any fee rates
+    pub fee_rounding: FeeRounding,
+    /// Flat fee paid by the funding account of each offer made, in lamports (see
+    /// `LamportFeeVault`)
+    pub make_lamport_fee: u64,
+    /// Flat fee paid by the funding account of each take, in lamports (see `LamportFeeVault`)
+    pub take_lamport_fee: u64,
+    /// Fee change delay decreased with `SetFeeChangeDelay`, replacing the delay above once the
+    /// latter has passed
+    pub pending_fee_change_delay: Option<PendingFeeChangeDelay>,
+}
+
+impl ProgramAccount for EscrowState {
+    const ACCOUNT_TYPE: AccountType = AccountType::EscrowState;
+    const VERSION: u8 = 1;
+    const LEN: usize = 512;
 }
 
 impl EscrowState {
@@ -28,10 +220,25 @@ impl EscrowState {
         let (address, bump) = Self::find_program_address(program_id);
         (
             Self {
+                account_type: AccountType::EscrowState,
+                version: Self::VERSION,
                 manager,
                 maker_fee_bps,
                 taker_fee_bps,
                 bump,
+                pending_manager: None,
+                is_make_paused: false,
+                is_take_paused: false,
+                fee_policy: FeePolicy::AtTake,
+                pending_fees: None,
+                fee_change_delay: 0,
+                fee_collector: None,
+                fee_destination: None,
+                referral_share_bps: 0,
+                fee_rounding: FeeRounding::default(),
+                make_lamport_fee: 0,
+                take_lamport_fee: 0,
+                pending_fee_change_delay: None,
             },
             address,
         )
@@ -49,70 +256,196 @@ impl EscrowState {
         Pubkey::create_program_address(escrow_state_signer_seed, program_id)
     }
 
-    /// Calculate token A (offer token) fee amount.
+    /// Programwide fee rates (unless overridden for the mints of an offer) and fee rounding in
+    /// effect at the given unix timestamp.
+    pub fn fee_rates(&self, unix_timestamp: i64) -> (FeeRates, FeeRounding) {
+        match self.pending_fees {
+            Some(pending_fees) if unix_timestamp >= pending_fees.effective_at => {
+                (pending_fees.fee_rates, pending_fees.fee_rounding)
+            }
+            _ => (self.current_fee_rates(), self.fee_rounding),
+        }
+    }
+
+    /// Replace the fee rates with the pending fees, if in effect at the given unix timestamp.
+    pub fn apply_pending_fees(&mut self, unix_timestamp: i64) {
+        if let Some(pending_fees) = self
+            .pending_fees
+            .filter(|pending_fees| unix_timestamp >= pending_fees.effective_at)
+        {
+            self.maker_fee_bps = pending_fees.fee_rates.maker_fee_bps;
+            self.taker_fee_bps = pending_fees.fee_rates.taker_fee_bps;
+            self.fee_rounding = pending_fees.fee_rounding;
+            self.pending_fees = None;
+        }
+    }
+
+    /// Replace the fee change delay with the pending one, if in effect at the given unix
+    /// timestamp.
+    pub fn apply_pending_fee_change_delay(&mut self, unix_timestamp: i64) {
+        if let Some(pending_fee_change_delay) =
+            self.pending_fee_change_delay
+                .filter(|pending_fee_change_delay| {
+                    unix_timestamp >= pending_fee_change_delay.effective_at
+                })
+        {
+            self.fee_change_delay = pending_fee_change_delay.fee_change_delay;
+            self.pending_fee_change_delay = None;
+        }
+    }
+
+    fn current_fee_rates(&self) -> FeeRates {
+        FeeRates {
+            maker_fee_bps: self.maker_fee_bps,
+            taker_fee_bps: self.taker_fee_bps,
+        }
+    }
+
+    /// Calculate the share of a fee amount sent to the referrer of a take.
     ///
-    /// The fee is to be levied **from the amount transferred from vault to taker**.
+    /// The share is **rounded down**, regardless of the fee rounding.
+    pub fn get_referral_fee(&self, fee_amount: u64) -> Result<u64, EscrowError> {
+        FeeRounding::default().get_fee(fee_amount, self.referral_share_bps, None)
+    }
+
+    /// Calculate token A (offer token) fee amount, at the programwide fee rates (ignoring any
+    /// pending fees).
     pub fn get_token_a_fee(&self, amount: u64) -> Result<u64, EscrowError> {
-        u128::from(amount)
-            .checked_mul(u128::from(self.taker_fee_bps))
-            .and_then(|v| v.checked_div(u128::from(MAX_BPS_VALUE)))
-            .and_then(|v| u64::try_from(v).ok())
-            .ok_or(EscrowError::MathError)
+        self.current_fee_rates()
+            .get_token_a_fee(amount, &self.fee_rounding)
     }
 
-    /// Calculate token B (ask token) fee amount.
-    ///
-    /// The fee is to be levied **from the amount transferred from taker to maker**.
+    /// Calculate token B (ask token) fee amount, at the programwide fee rates (ignoring any
+    /// pending fees).
     pub fn get_token_b_fee(&self, amount: u64) -> Result<u64, EscrowError> {
-        u128::from(amount)
-            .checked_mul(u128::from(self.maker_fee_bps))
-            .and_then(|v| v.checked_div(u128::from(MAX_BPS_VALUE)))
-            .and_then(|v| u64::try_from(v).ok())
-            .ok_or(EscrowError::MathError)
+        self.current_fee_rates()
+            .get_token_b_fee(amount, &self.fee_rounding)
     }
 }
 
 /// Describes each offer made by maker.
 ///
-/// Also used to hold vault (ATA) until escrow offer is taken.
+/// Also used to hold vault (ATA) until escrow offer is fully taken.
+/// Both `token_a_offered_amount` and `token_b_wanted_amount` are reduced on
+/// each partial fill.
 ///
 ///
 /// PDA seed format: ["offer", maker_pubkey, offer_id]
 #[derive(BorshDeserialize, BorshSerialize, Debug)]
 pub struct Offer {
+    pub account_type: AccountType,
+    pub version: u8,
     pub id: u64,
     pub maker: Pubkey,
     pub token_a_mint: Pubkey,
     pub token_b_mint: Pubkey,
+    /// Amount of token A escrowed in the vault, net of any Token-2022 transfer fee
+    pub token_a_offered_amount: u64,
     pub token_b_wanted_amount: u64,
+    /// Unix timestamp after which the offer can no longer be taken
+    pub expires_at: Option<i64>,
+    /// Only taker allowed to take the offer (private offer), anyone if `None`
+    pub allowed_taker: Option<Pubkey>,
+    /// Merkle root of the takers allowed to take the offer (see `crate::merkle`), anyone if `None`
+    pub taker_allowlist_root: Option<[u8; 32]>,
+    /// Funding account of the offer, refunded with the rent on expiry
+    pub rent_payer: Pubkey,
     pub bump: u8,
+    /// Fee rates in effect when the offer was made, `None` for offers made before they were
+    /// recorded (see `FeePolicy`)
+    pub fee_rates: Option<FeeRates>,
+    /// Discount of the maker fee from the fee exemption of the maker when the offer was made,
+    /// in basis points of the maker fee rate
+    pub maker_fee_discount_bps: u16,
+    /// Fee rounding in effect when the offer was made, `None` for offers made before it was
+    /// recorded (see `FeePolicy`)
+    pub fee_rounding: Option<FeeRounding>,
+}
+
+impl ProgramAccount for Offer {
+    const ACCOUNT_TYPE: AccountType = AccountType::Offer;
+    const VERSION: u8 = 1;
+    const LEN: usize = 384;
 }
 
 impl Offer {
     pub const SEED_PREFIX: &'static [u8] = b"offer";
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         program_id: &Pubkey,
         offer_id: u64,
         maker_pubkey: Pubkey,
         token_a_mint_pubkey: Pubkey,
         token_b_mint_pubkey: Pubkey,
+        token_a_offered_amount: u64,
         token_b_wanted_amount: u64,
+        rent_payer_pubkey: Pubkey,
     ) -> (Self, Pubkey) {
         let (address, bump) = Self::find_program_address(program_id, &maker_pubkey, offer_id);
         (
             Self {
+                account_type: AccountType::Offer,
+                version: Self::VERSION,
                 id: offer_id,
                 maker: maker_pubkey,
                 token_a_mint: token_a_mint_pubkey,
                 token_b_mint: token_b_mint_pubkey,
+                token_a_offered_amount,
                 token_b_wanted_amount,
+                expires_at: None,
+                allowed_taker: None,
+                taker_allowlist_root: None,
+                rent_payer: rent_payer_pubkey,
                 bump,
+                fee_rates: None,
+                maker_fee_discount_bps: 0,
+                fee_rounding: None,
             },
             address,
         )
     }
 
+    /// Whether the offer has expired at the given unix timestamp.
+    pub fn is_expired(&self, unix_timestamp: i64) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| unix_timestamp >= expires_at)
+    }
+
+    /// Whether the given taker is allowed to take the offer.
+    ///
+    /// The proof is only checked against the taker allowlist, if the offer has one.
+    pub fn is_allowed_taker(
+        &self,
+        taker_pubkey: &Pubkey,
+        taker_allowlist_proof: &[[u8; 32]],
+    ) -> bool {
+        let is_allowed_taker = match self.allowed_taker {
+            Some(allowed_taker) => allowed_taker == *taker_pubkey,
+            None => true,
+        };
+        let is_in_taker_allowlist = match self.taker_allowlist_root {
+            Some(root) => merkle::verify_proof(&root, taker_allowlist_proof, taker_pubkey),
+            None => true,
+        };
+
+        is_allowed_taker && is_in_taker_allowlist
+    }
+
+    /// Calculate token B amount to be paid for a share of the remaining token A.
+    ///
+    /// The amount is **rounded up** so that partial fills never underpay the maker.
+    pub fn get_token_b_amount(&self, token_a_amount: u64) -> Result<u64, EscrowError> {
+        let token_a_offered_amount = u128::from(self.token_a_offered_amount);
+
+        u128::from(token_a_amount)
+            .checked_mul(u128::from(self.token_b_wanted_amount))
+            .and_then(|v| v.checked_add(token_a_offered_amount.checked_sub(1)?))
+            .and_then(|v| v.checked_div(token_a_offered_amount))
+            .and_then(|v| u64::try_from(v).ok())
+            .ok_or(EscrowError::MathError)
+    }
+
     pub fn find_program_address(
         program_id: &Pubkey,
         maker_pubkey: &Pubkey,
@@ -143,3 +476,425 @@ impl Offer {
         Pubkey::create_program_address(offer_signer_seed, program_id)
     }
 }
+
+/// Fee rates set by the manager for a mint pair, or for any offer of a single mint.
+///
+/// The override for the mint pair of an offer takes precedence over the one for its token A,
+/// then over the one for its token B (see `crate::utils::load_fee_rates`).
+///
+/// PDA seed format: ["fee_override", mint] or ["fee_override", mint, paired_mint]
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct FeeOverride {
+    pub account_type: AccountType,
+    pub version: u8,
+    /// Token A mint of the offers (pair), or either mint of the offers (single mint)
+    pub mint: Pubkey,
+    /// Token B mint of the offers, if the override is for a mint pair
+    pub paired_mint: Option<Pubkey>,
+    pub maker_fee_bps: u16,
+    pub taker_fee_bps: u16,
+    pub bump: u8,
+}
+
+impl ProgramAccount for FeeOverride {
+    const ACCOUNT_TYPE: AccountType = AccountType::FeeOverride;
+    const VERSION: u8 = 1;
+    const LEN: usize = 128;
+}
+
+impl FeeOverride {
+    pub const SEED_PREFIX: &'static [u8] = b"fee_override";
+
+    pub fn new(
+        program_id: &Pubkey,
+        mint: Pubkey,
+        paired_mint: Option<Pubkey>,
+        maker_fee_bps: u16,
+        taker_fee_bps: u16,
+    ) -> (Self, Pubkey) {
+        let (address, bump) = Self::find_program_address(program_id, &mint, paired_mint.as_ref());
+        (
+            Self {
+                account_type: AccountType::FeeOverride,
+                version: Self::VERSION,
+                mint,
+                paired_mint,
+                maker_fee_bps,
+                taker_fee_bps,
+                bump,
+            },
+            address,
+        )
+    }
+
+    pub fn fee_rates(&self) -> FeeRates {
+        FeeRates {
+            maker_fee_bps: self.maker_fee_bps,
+            taker_fee_bps: self.taker_fee_bps,
+        }
+    }
+
+    pub fn find_program_address(
+        program_id: &Pubkey,
+        mint: &Pubkey,
+        paired_mint: Option<&Pubkey>,
+    ) -> (Pubkey, u8) {
+        match paired_mint {
+            Some(paired_mint) => Pubkey::find_program_address(
+                &[Self::SEED_PREFIX, mint.as_ref(), paired_mint.as_ref()],
+                program_id,
+            ),
+            None => Pubkey::find_program_address(&[Self::SEED_PREFIX, mint.as_ref()], program_id),
+        }
+    }
+
+    pub fn create_program_address(&self, program_id: &Pubkey) -> Result<Pubkey, PubkeyError> {
+        match self.paired_mint {
+            Some(paired_mint) => Pubkey::create_program_address(
+                &[
+                    Self::SEED_PREFIX,
+                    self.mint.as_ref(),
+                    paired_mint.as_ref(),
+                    &[self.bump],
+                ],
+                program_id,
+            ),
+            None => Pubkey::create_program_address(
+                &[Self::SEED_PREFIX, self.mint.as_ref(), &[self.bump]],
+                program_id,
+            ),
+        }
+    }
+}
+
+/// Discount of the fees of a wallet (e.g. market makers), managed by the manager.
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct FeeExemption {
+    pub account_type: AccountType,
+    pub version: u8,
+    /// Maker or taker whose fees are discounted
+    pub wallet: Pubkey,
+    /// Discount of the maker and taker fee rates, in basis points of the rates (full exemption
+    /// at `MAX_BPS_VALUE`)
+    pub discount_bps: u16,
+    /// Optional unix timestamp from which the exemption no longer applies
+    pub expires_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl ProgramAccount for FeeExemption {
+    const ACCOUNT_TYPE: AccountType = AccountType::FeeExemption;
+    const VERSION: u8 = 1;
+    const LEN: usize = 96;
+}
+
+impl FeeExemption {
+    pub const SEED_PREFIX: &'static [u8] = b"fee_exemption";
+
+    pub fn new(
+        program_id: &Pubkey,
+        wallet: Pubkey,
+        discount_bps: u16,
+        expires_at: Option<i64>,
+    ) -> (Self, Pubkey) {
+        let (address, bump) = Self::find_program_address(program_id, &wallet);
+        (
+            Self {
+                account_type: AccountType::FeeExemption,
+                version: Self::VERSION,
+                wallet,
+                discount_bps,
+                expires_at,
+                bump,
+            },
+            address,
+        )
+    }
+
+    /// Discount in effect at the given time, zero once the exemption has expired.
+    pub fn discount_bps(&self, unix_timestamp: i64) -> u16 {
+        match self.expires_at {
+            Some(expires_at) if unix_timestamp >= expires_at => 0,
+            _ => self.discount_bps,
+        }
+    }
+
+    pub fn find_program_address(program_id: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, wallet.as_ref()], program_id)
+    }
+
+    pub fn create_program_address(&self, program_id: &Pubkey) -> Result<Pubkey, PubkeyError> {
+        Pubkey::create_program_address(
+            &[Self::SEED_PREFIX, self.wallet.as_ref(), &[self.bump]],
+            program_id,
+        )
+    }
+}
+
+/// Program-owned account collecting the flat fees of makes and takes, in lamports (on top of
+/// its rent).
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct LamportFeeVault {
+    pub account_type: AccountType,
+    pub version: u8,
+    pub bump: u8,
+}
+
+impl ProgramAccount for LamportFeeVault {
+    const ACCOUNT_TYPE: AccountType = AccountType::LamportFeeVault;
+    const VERSION: u8 = 1;
+    const LEN: usize = 16;
+}
+
+impl LamportFeeVault {
+    pub const SEED: &'static [u8] = b"lamport_fee_vault";
+
+    pub fn new(program_id: &Pubkey) -> (Self, Pubkey) {
+        let (address, bump) = Self::find_program_address(program_id);
+        (
+            Self {
+                account_type: AccountType::LamportFeeVault,
+                version: Self::VERSION,
+                bump,
+            },
+            address,
+        )
+    }
+
+    pub fn find_program_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+
+    pub fn create_program_address(program_id: &Pubkey, bump: u8) -> Result<Pubkey, PubkeyError> {
+        Pubkey::create_program_address(&[Self::SEED, &[bump]], program_id)
+    }
+}
+
+/// Layout of the escrow state before accounts were versioned (see `MigrateState`).
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct LegacyEscrowState {
+    pub manager: Pubkey,
+    pub maker_fee_bps: u16,
+    pub taker_fee_bps: u16,
+    pub bump: u8,
+}
+
+impl LegacyEscrowState {
+    pub const LEN: usize = 32 + 2 + 2 + 1;
+}
+
+/// Layout of offers before accounts were versioned (see `MigrateOffer`).
+///
+/// The offered amount was not recorded, it is read from the vault on migration.
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct LegacyOffer {
+    pub id: u64,
+    pub maker: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_b_wanted_amount: u64,
+    pub bump: u8,
+}
+
+impl LegacyOffer {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOOR: FeeRounding = FeeRounding {
+        mode: RoundingMode::Floor,
+        min_maker_fee: None,
+        min_taker_fee: None,
+    };
+    const CEIL: FeeRounding = FeeRounding {
+        mode: RoundingMode::Ceil,
+        ..FLOOR
+    };
+    const HALF_UP: FeeRounding = FeeRounding {
+        mode: RoundingMode::HalfUp,
+        ..FLOOR
+    };
+
+    #[test]
+    fn it_rounds_fees_down() {
+        assert_eq!(FLOOR.get_fee(0, 5, None).unwrap(), 0);
+        assert_eq!(FLOOR.get_fee(69, 5, None).unwrap(), 0);
+        assert_eq!(FLOOR.get_fee(1_999, 5, None).unwrap(), 0);
+        assert_eq!(FLOOR.get_fee(2_000, 5, None).unwrap(), 1);
+        assert_eq!(FLOOR.get_fee(2_001, 5, None).unwrap(), 1);
+        assert_eq!(FLOOR.get_fee(3_999, 5, None).unwrap(), 1);
+        assert_eq!(FLOOR.get_fee(4_000, 5, None).unwrap(), 2);
+    }
+
+    #[test]
+    fn it_rounds_fees_up() {
+        assert_eq!(CEIL.get_fee(0, 5, None).unwrap(), 0);
+        assert_eq!(CEIL.get_fee(1, 5, None).unwrap(), 1);
+        assert_eq!(CEIL.get_fee(69, 5, None).unwrap(), 1);
+        assert_eq!(CEIL.get_fee(2_000, 5, None).unwrap(), 1);
+        assert_eq!(CEIL.get_fee(2_001, 5, None).unwrap(), 2);
+        assert_eq!(CEIL.get_fee(4_000, 5, None).unwrap(), 2);
+        assert_eq!(CEIL.get_fee(4_001, 5, None).unwrap(), 3);
+    }
+
+    #[test]
+    fn it_rounds_fees_half_up() {
+        assert_eq!(HALF_UP.get_fee(0, 5, None).unwrap(), 0);
+        assert_eq!(HALF_UP.get_fee(69, 5, None).unwrap(), 0);
+        assert_eq!(HALF_UP.get_fee(999, 5, None).unwrap(), 0);
+        assert_eq!(HALF_UP.get_fee(1_000, 5, None).unwrap(), 1);
+        assert_eq!(HALF_UP.get_fee(2_000, 5, None).unwrap(), 1);
+        assert_eq!(HALF_UP.get_fee(2_999, 5, None).unwrap(), 1);
+        assert_eq!(HALF_UP.get_fee(3_000, 5, None).unwrap(), 2);
+    }
+
+    #[test]
+    fn it_rounds_fees_at_rate_bounds() {
+        for fee_rounding in [FLOOR, CEIL, HALF_UP] {
+            assert_eq!(fee_rounding.get_fee(u64::MAX, 0, None).unwrap(), 0);
+            assert_eq!(
+                fee_rounding.get_fee(u64::MAX, MAX_BPS_VALUE, None).unwrap(),
+                u64::MAX
+            );
+            assert_eq!(fee_rounding.get_fee(1, MAX_BPS_VALUE, None).unwrap(), 1);
+            assert_eq!(fee_rounding.get_fee(0, MAX_BPS_VALUE, None).unwrap(), 0);
+        }
+        assert_eq!(FLOOR.get_fee(u64::MAX, 1, None).unwrap(), u64::MAX / 10_000);
+        assert_eq!(
+            CEIL.get_fee(u64::MAX, 1, None).unwrap(),
+            u64::MAX / 10_000 + 1
+        );
+    }
+
+    #[test]
+    fn it_applies_minimum_fee() {
+        for fee_rounding in [FLOOR, CEIL, HALF_UP] {
+            // Raised to the minimum
+            assert_eq!(fee_rounding.get_fee(69, 5, Some(3)).unwrap(), 3);
+            // Above the minimum
+            assert_eq!(fee_rounding.get_fee(100_000, 5, Some(3)).unwrap(), 50);
+            // At the minimum
+            assert_eq!(fee_rounding.get_fee(6_000, 5, Some(3)).unwrap(), 3);
+            // Capped at the amount
+            assert_eq!(fee_rounding.get_fee(2, 5, Some(3)).unwrap(), 2);
+            assert_eq!(fee_rounding.get_fee(0, 5, Some(3)).unwrap(), 0);
+            // Not applied at a zero fee rate
+            assert_eq!(fee_rounding.get_fee(69, 0, Some(3)).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn it_calculates_fees_per_side() {
+        let fee_rates = FeeRates {
+            maker_fee_bps: 5,
+            taker_fee_bps: 10,
+        };
+        let fee_rounding = FeeRounding {
+            mode: RoundingMode::Ceil,
+            min_maker_fee: Some(2),
+            min_taker_fee: Some(4),
+        };
+
+        assert_eq!(fee_rates.get_token_b_fee(69, &fee_rounding).unwrap(), 2);
+        assert_eq!(fee_rates.get_token_a_fee(69, &fee_rounding).unwrap(), 4);
+        assert_eq!(fee_rates.get_token_b_fee(69, &FLOOR).unwrap(), 0);
+        assert_eq!(fee_rates.get_token_a_fee(69, &FLOOR).unwrap(), 0);
+        assert_eq!(fee_rates.get_token_b_fee(2_001, &CEIL).unwrap(), 2);
+        assert_eq!(fee_rates.get_token_a_fee(2_001, &CEIL).unwrap(), 3);
+    }
+
+    #[test]
+    fn it_discounts_fee_rates() {
+        let fee_rates = FeeRates {
+            maker_fee_bps: 100,
+            taker_fee_bps: 5,
+        };
+
+        assert_eq!(fee_rates.discounted(0, 0), fee_rates);
+        assert_eq!(
+            fee_rates.discounted(MAX_BPS_VALUE, 5_000),
+            FeeRates {
+                maker_fee_bps: 0,
+                taker_fee_bps: 2,
+            }
+        );
+        assert_eq!(
+            fee_rates.discounted(2_500, MAX_BPS_VALUE),
+            FeeRates {
+                maker_fee_bps: 75,
+                taker_fee_bps: 0,
+            }
+        );
+        assert_eq!(
+            fee_rates.discounted(u16::MAX, u16::MAX),
+            FeeRates {
+                maker_fee_bps: 0,
+                taker_fee_bps: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn it_expires_fee_exemption() {
+        let (fee_exemption, _) =
+            FeeExemption::new(&crate::ID, Pubkey::new_unique(), 5_000, Some(100));
+        assert_eq!(fee_exemption.discount_bps(99), 5_000);
+        assert_eq!(fee_exemption.discount_bps(100), 0);
+
+        let (fee_exemption, _) = FeeExemption::new(&crate::ID, Pubkey::new_unique(), 5_000, None);
+        assert_eq!(fee_exemption.discount_bps(i64::MAX), 5_000);
+    }
+
+    #[test]
+    fn it_returns_pending_fee_rounding_with_fee_rates() {
+        let (mut escrow_state, _) = EscrowState::new(&crate::ID, Pubkey::new_unique(), 5, 5);
+        let fee_rates = FeeRates {
+            maker_fee_bps: 10,
+            taker_fee_bps: 20,
+        };
+        let fee_rounding = FeeRounding {
+            mode: RoundingMode::Ceil,
+            min_maker_fee: None,
+            min_taker_fee: Some(1),
+        };
+        escrow_state.pending_fees = Some(PendingFees {
+            fee_rates,
+            effective_at: 100,
+            fee_rounding,
+        });
+
+        assert_eq!(
+            escrow_state.fee_rates(99),
+            (escrow_state.current_fee_rates(), FeeRounding::default())
+        );
+        assert_eq!(escrow_state.fee_rates(100), (fee_rates, fee_rounding));
+    }
+
+    #[test]
+    fn it_applies_pending_fee_rounding() {
+        let (mut escrow_state, _) = EscrowState::new(&crate::ID, Pubkey::new_unique(), 5, 5);
+        let fee_rounding = FeeRounding {
+            mode: RoundingMode::HalfUp,
+            min_maker_fee: Some(1),
+            min_taker_fee: None,
+        };
+        escrow_state.pending_fees = Some(PendingFees {
+            fee_rates: escrow_state.current_fee_rates(),
+            effective_at: 100,
+            fee_rounding,
+        });
+
+        escrow_state.apply_pending_fees(99);
+        assert_eq!(escrow_state.fee_rounding, FeeRounding::default());
+        assert_eq!(escrow_state.get_token_b_fee(69).unwrap(), 0);
+
+        escrow_state.apply_pending_fees(100);
+        assert_eq!(escrow_state.fee_rounding, fee_rounding);
+        assert_eq!(escrow_state.get_token_b_fee(69).unwrap(), 1);
+        assert_eq!(escrow_state.get_token_a_fee(69).unwrap(), 0);
+        assert_eq!(escrow_state.get_token_a_fee(1_000).unwrap(), 1);
+    }
+}
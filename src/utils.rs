@@ -1,17 +1,43 @@
 use solana_program::{
-    account_info::AccountInfo, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{instruction::harvest_withheld_tokens_to_mint, TransferFeeConfig},
+        transfer_hook, BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::{Account as TokenAccount, Mint},
 };
-use spl_token::state::Account as TokenAccount;
 
-use crate::{consts::MAX_BPS_VALUE, errors::EscrowError};
+use crate::{
+    consts::MAX_BPS_VALUE,
+    errors::EscrowError,
+    state::{
+        EscrowState, FeeExemption, FeeOverride, FeeRates, FeeRounding, LamportFeeVault,
+        ProgramAccount,
+    },
+};
 
 pub fn assert_is_associated_token_account(
     token_account_address: &Pubkey,
     owner: &Pubkey,
     mint: &Pubkey,
+    token_program_id: &Pubkey,
 ) -> Result<(), ProgramError> {
     let associated_token_account_address =
-        &spl_associated_token_account::get_associated_token_address(owner, mint);
+        &spl_associated_token_account::get_associated_token_address_with_program_id(
+            owner,
+            mint,
+            token_program_id,
+        );
 
     if token_account_address != associated_token_account_address {
         return Err(EscrowError::TokenAccountMismatch.into());
@@ -25,7 +51,7 @@ pub fn assert_token_account_mint_and_owner(
     owner: &Pubkey,
     mint: &Pubkey,
 ) -> Result<(), ProgramError> {
-    let token_account = TokenAccount::unpack(&token_account_info.data.borrow())?;
+    let token_account = unpack_token_account(token_account_info)?;
     if token_account.mint != *mint || token_account.owner != *owner {
         return Err(EscrowError::TokenAccountMismatch.into());
     }
@@ -33,6 +59,206 @@ pub fn assert_token_account_mint_and_owner(
     Ok(())
 }
 
+/// Ensure the token program is either SPL Token or Token-2022, and owns the mint.
+pub fn assert_is_token_program_of_mint(
+    token_program_info: &AccountInfo,
+    mint_info: &AccountInfo,
+) -> Result<(), ProgramError> {
+    let token_program_id = token_program_info.key;
+    if (*token_program_id != spl_token::ID && *token_program_id != spl_token_2022::ID)
+        || mint_info.owner != token_program_id
+    {
+        return Err(EscrowError::TokenProgramMismatch.into());
+    }
+
+    Ok(())
+}
+
+/// Unpack the base state of an SPL Token or Token-2022 account, ignoring extensions.
+pub fn unpack_token_account(
+    token_account_info: &AccountInfo,
+) -> Result<TokenAccount, ProgramError> {
+    let data = token_account_info.data.borrow();
+
+    Ok(StateWithExtensions::<TokenAccount>::unpack(&data)?.base)
+}
+
+pub fn get_mint_decimals(mint_info: &AccountInfo) -> Result<u8, ProgramError> {
+    let data = mint_info.data.borrow();
+
+    Ok(StateWithExtensions::<Mint>::unpack(&data)?.base.decimals)
+}
+
+/// Calculate the Token-2022 transfer fee withheld when transferring the given amount.
+///
+/// Returns zero for mints without the TransferFee extension.
+pub fn get_transfer_fee(mint_info: &AccountInfo, amount: u64) -> Result<u64, ProgramError> {
+    let data = mint_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(0);
+    };
+
+    transfer_fee_config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(EscrowError::MathError.into())
+}
+
+/// Whether the mint has a Token-2022 transfer hook program set.
+pub fn has_transfer_hook(mint_info: &AccountInfo) -> Result<bool, ProgramError> {
+    let data = mint_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    Ok(transfer_hook::get_program_id(&mint).is_some())
+}
+
+/// Calculate the amount to transfer so that the destination receives exactly the given
+/// amount after the Token-2022 transfer fee.
+///
+/// Returns the given amount for mints without the TransferFee extension.
+pub fn get_amount_with_transfer_fee(
+    mint_info: &AccountInfo,
+    amount: u64,
+) -> Result<u64, ProgramError> {
+    let data = mint_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(amount);
+    };
+
+    transfer_fee_config
+        .get_epoch_fee(Clock::get()?.epoch)
+        .calculate_pre_fee_amount(amount)
+        .ok_or(EscrowError::MathError.into())
+}
+
+/// Move Token-2022 transfer fees withheld in the token account to the mint, so that the token
+/// account can be closed.
+///
+/// Noop for mints without the TransferFee extension.
+pub fn harvest_withheld_transfer_fees<'a>(
+    token_program_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    token_account_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let has_transfer_fee = {
+        let data = mint_info.data.borrow();
+        let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+        mint.get_extension::<TransferFeeConfig>().is_ok()
+    };
+    if !has_transfer_fee {
+        return Ok(());
+    }
+
+    invoke(
+        &harvest_withheld_tokens_to_mint(
+            token_program_info.key,
+            mint_info.key,
+            &[token_account_info.key],
+        )?,
+        //   0. `[writable]` The mint.
+        //   1. ..1+N `[writable]` The source accounts to harvest from.
+        &[
+            mint_info.clone(),
+            token_account_info.clone(),
+            token_program_info.clone(),
+        ],
+    )
+}
+
+/// Whether the mint is the native mint (wrapped SOL) of SPL Token or Token-2022.
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::ID || *mint == spl_token_2022::native_mint::ID
+}
+
+/// Wrap SOL by transferring lamports from a system account into a native token account.
+pub fn wrap_native<'a>(
+    token_program_info: &AccountInfo<'a>,
+    from_info: &AccountInfo<'a>,
+    token_account_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    lamports: u64,
+) -> ProgramResult {
+    invoke(
+        &system_instruction::transfer(from_info.key, token_account_info.key, lamports),
+        //   0. `[WRITE, SIGNER]` Funding account
+        //   1. `[WRITE]` Recipient account
+        &[
+            from_info.clone(),
+            token_account_info.clone(),
+            system_program_info.clone(),
+        ],
+    )?;
+
+    invoke(
+        &spl_token_2022::instruction::sync_native(token_program_info.key, token_account_info.key)?,
+        //   0. `[writable]`  The native token account to sync with its underlying lamports.
+        &[token_account_info.clone(), token_program_info.clone()],
+    )
+}
+
+/// Unwrap SOL by closing a native token account, sending all its lamports to its owner
+/// (signing with the given seeds if the owner is a PDA).
+pub fn unwrap_native<'a>(
+    token_program_info: &AccountInfo<'a>,
+    token_account_info: &AccountInfo<'a>,
+    owner_info: &AccountInfo<'a>,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    invoke_signed(
+        &spl_token_2022::instruction::close_account(
+            token_program_info.key,
+            token_account_info.key,
+            owner_info.key,
+            owner_info.key,
+            &[],
+        )?,
+        //   0. `[writable]` The account to close.
+        //   1. `[writable]` The destination account.
+        //   2. `[signer]` The account's owner.
+        &[
+            token_account_info.clone(),
+            owner_info.clone(),
+            token_program_info.clone(),
+        ],
+        signers_seeds,
+    )
+}
+
+/// Pay a flat fee by transferring lamports from a system account into the lamport fee vault,
+/// if the fee is nonzero.
+pub fn pay_lamport_fee<'a>(
+    program_id: &Pubkey,
+    lamport_fee_vault_info: &AccountInfo<'a>,
+    from_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    lamports: u64,
+) -> ProgramResult {
+    if lamports == 0 {
+        return Ok(());
+    }
+
+    // Ensure the provided lamport fee vault address is correct
+    let lamport_fee_vault =
+        load_program_account::<LamportFeeVault>(program_id, lamport_fee_vault_info)?;
+    if *lamport_fee_vault_info.key
+        != LamportFeeVault::create_program_address(program_id, lamport_fee_vault.bump)?
+    {
+        return Err(EscrowError::LamportFeeVaultKeyMismatch.into());
+    }
+
+    invoke(
+        &system_instruction::transfer(from_info.key, lamport_fee_vault_info.key, lamports),
+        //   0. `[WRITE, SIGNER]` Funding account
+        //   1. `[WRITE]` Recipient account
+        &[
+            from_info.clone(),
+            lamport_fee_vault_info.clone(),
+            system_program_info.clone(),
+        ],
+    )
+}
+
 pub fn assert_is_bps_in_range(bps: u16) -> Result<(), ProgramError> {
     if bps > MAX_BPS_VALUE {
         return Err(EscrowError::MaxBpsValueExceeded.into());
@@ -41,9 +267,176 @@ pub fn assert_is_bps_in_range(bps: u16) -> Result<(), ProgramError> {
     Ok(())
 }
 
+/// Deserialize a program-owned account, after checking its owner, account type, size and version.
+pub fn load_program_account<T: ProgramAccount>(
+    program_id: &Pubkey,
+    account_info: &AccountInfo,
+) -> Result<T, ProgramError> {
+    if account_info.owner != program_id {
+        return Err(EscrowError::AccountOwnerMismatch.into());
+    }
+
+    let data = account_info.data.borrow();
+    if data.first() != Some(&(T::ACCOUNT_TYPE as u8)) {
+        return Err(EscrowError::AccountTypeMismatch.into());
+    }
+    if data.len() != T::LEN {
+        return Err(EscrowError::AccountSizeMismatch.into());
+    }
+    if data.get(1) != Some(&T::VERSION) {
+        return Err(EscrowError::AccountVersionMismatch.into());
+    }
+
+    // NOTE: The reserved bytes past the serialized fields are left unread
+    Ok(T::deserialize(&mut &data[..])?)
+}
+
+/// Serialize a program-owned account, zeroing its reserved bytes.
+pub fn save_program_account<T: ProgramAccount>(
+    account_info: &AccountInfo,
+    account: &T,
+) -> ProgramResult {
+    let mut data = account_info.data.borrow_mut();
+    data.fill(0);
+    account.serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+/// Resize a program-owned account, topping up its rent exemption from the payer.
+pub fn realloc_program_account<'a>(
+    account_info: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    len: usize,
+) -> ProgramResult {
+    let lamports_required = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(account_info.lamports());
+    if lamports_required > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account_info.key, lamports_required),
+            //   0. `[WRITE, SIGNER]` Funding account
+            //   1. `[WRITE]` Recipient account
+            &[payer.clone(), account_info.clone(), system_program.clone()],
+        )?;
+    }
+
+    account_info.realloc(len, true)
+}
+
+/// Create a program-owned PDA account, even if already funded with lamports, by topping up its
+/// rent exemption from the payer, then allocating and assigning it.
+pub fn create_program_account<'a>(
+    program_id: &Pubkey,
+    account_info: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    len: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let lamports_required = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(account_info.lamports());
+    if lamports_required > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account_info.key, lamports_required),
+            //   0. `[WRITE, SIGNER]` Funding account
+            //   1. `[WRITE]` Recipient account
+            &[payer.clone(), account_info.clone(), system_program.clone()],
+        )?;
+    }
+
+    invoke_signed(
+        &system_instruction::allocate(account_info.key, len as u64),
+        //   0. `[WRITE, SIGNER]` New account
+        &[account_info.clone(), system_program.clone()],
+        &[signer_seeds],
+    )?;
+
+    invoke_signed(
+        &system_instruction::assign(account_info.key, program_id),
+        //   0. `[WRITE, SIGNER]` Assigned account public key
+        &[account_info.clone(), system_program.clone()],
+        &[signer_seeds],
+    )
+}
+
+/// Fee rates for an offer of the given mints, from the fee override for the mint pair, else for
+/// token A, else for token B, or the programwide fees in effect if none of them exists. Along
+/// with the programwide fee rounding in effect, which applies to fee overrides as well.
+///
+/// The fee override accounts are expected in that order, and are required even if uninitialized
+/// so that an existing fee override cannot be left out.
+pub fn load_fee_rates(
+    program_id: &Pubkey,
+    escrow_state: &EscrowState,
+    fee_override_infos: [&AccountInfo; 3],
+    token_a_mint: &Pubkey,
+    token_b_mint: &Pubkey,
+) -> Result<(FeeRates, FeeRounding), ProgramError> {
+    let (fee_rates, fee_rounding) = escrow_state.fee_rates(Clock::get()?.unix_timestamp);
+
+    let fee_override_mints = [
+        (token_a_mint, Some(token_b_mint)),
+        (token_a_mint, None),
+        (token_b_mint, None),
+    ];
+
+    for (fee_override_info, (mint, paired_mint)) in
+        fee_override_infos.into_iter().zip(fee_override_mints)
+    {
+        // Ensure the provided fee override address is correct
+        let (fee_override_address, _) =
+            FeeOverride::find_program_address(program_id, mint, paired_mint);
+        if *fee_override_info.key != fee_override_address {
+            return Err(EscrowError::FeeOverrideKeyMismatch.into());
+        }
+
+        // Use the first fee override that exists
+        if fee_override_info.owner == program_id {
+            let fee_override = load_program_account::<FeeOverride>(program_id, fee_override_info)?;
+            return Ok((fee_override.fee_rates(), fee_rounding));
+        }
+    }
+
+    Ok((fee_rates, fee_rounding))
+}
+
+/// Fee discount of the given wallet in effect, from the fee exemption account if provided, or
+/// none otherwise.
+pub fn load_fee_discount(
+    program_id: &Pubkey,
+    fee_exemption_info: Option<&AccountInfo>,
+    wallet: &Pubkey,
+) -> Result<u16, ProgramError> {
+    let Some(fee_exemption_info) = fee_exemption_info else {
+        return Ok(0);
+    };
+
+    let fee_exemption = load_program_account::<FeeExemption>(program_id, fee_exemption_info)?;
+
+    // Ensure the provided fee exemption address is correct
+    if *fee_exemption_info.key != fee_exemption.create_program_address(program_id)? {
+        return Err(EscrowError::FeeExemptionKeyMismatch.into());
+    }
+
+    // Ensure the fee exemption applies to the wallet
+    if fee_exemption.wallet != *wallet {
+        return Err(EscrowError::FeeExemptionWalletMismatch.into());
+    }
+
+    Ok(fee_exemption.discount_bps(Clock::get()?.unix_timestamp))
+}
+
 #[cfg(test)]
 mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use borsh::BorshSerialize;
+
     use super::*;
+    use crate::state::{EscrowState, Offer};
 
     #[test]
     fn it_checks_bps() {
@@ -53,4 +446,55 @@ mod tests {
         assert!(assert_is_bps_in_range(10_001).is_err());
         assert!(assert_is_bps_in_range(20_000).is_err());
     }
+
+    #[test]
+    fn it_loads_program_account() {
+        let program_id = Pubkey::new_unique();
+        let (escrow_state, address) = EscrowState::new(&program_id, Pubkey::new_unique(), 1, 2);
+        let mut lamports = 0;
+        let mut data = vec![0; EscrowState::LEN];
+        let mut oversized_data = vec![0; EscrowState::LEN + 1];
+        escrow_state.serialize(&mut &mut data[..]).unwrap();
+        escrow_state
+            .serialize(&mut &mut oversized_data[..])
+            .unwrap();
+        let mut account_info = AccountInfo::new(
+            &address,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+
+        let loaded = load_program_account::<EscrowState>(&program_id, &account_info).unwrap();
+        assert_eq!(loaded.manager, escrow_state.manager);
+
+        assert_eq!(
+            load_program_account::<Offer>(&program_id, &account_info).unwrap_err(),
+            EscrowError::AccountTypeMismatch.into()
+        );
+
+        let other_program_id = Pubkey::new_unique();
+        account_info.owner = &other_program_id;
+        assert_eq!(
+            load_program_account::<EscrowState>(&program_id, &account_info).unwrap_err(),
+            EscrowError::AccountOwnerMismatch.into()
+        );
+        account_info.owner = &program_id;
+
+        account_info.data.borrow_mut()[1] = EscrowState::VERSION + 1;
+        assert_eq!(
+            load_program_account::<EscrowState>(&program_id, &account_info).unwrap_err(),
+            EscrowError::AccountVersionMismatch.into()
+        );
+
+        account_info.data = Rc::new(RefCell::new(&mut oversized_data[..]));
+        assert_eq!(
+            load_program_account::<EscrowState>(&program_id, &account_info).unwrap_err(),
+            EscrowError::AccountSizeMismatch.into()
+        );
+    }
 }
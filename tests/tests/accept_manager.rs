@@ -0,0 +1,89 @@
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+use escrow_program::{errors::EscrowError, instructions::accept_manager_ix, state::EscrowState};
+
+use crate::utils::{add_escrow_state_account, get_program_account, prepare_program_test};
+
+#[tokio::test]
+async fn it_accepts_manager() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account, with a nominated manager
+    let new_manager_keypair = Keypair::new();
+    let (mut escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0);
+    escrow_state.pending_manager = Some(new_manager_keypair.pubkey());
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call accept manager instruction
+    let accept_manager_instruction = accept_manager_ix(&new_manager_keypair.pubkey());
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[accept_manager_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &new_manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state_after_accept =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+
+    assert_eq!(
+        escrow_state_after_accept.manager,
+        new_manager_keypair.pubkey()
+    );
+    assert_eq!(escrow_state_after_accept.pending_manager, None);
+}
+
+#[tokio::test]
+async fn it_fails_to_accept_manager_by_other_key() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account, with a nominated manager
+    let (mut escrow_state, _escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0);
+    escrow_state.pending_manager = Some(Pubkey::new_unique());
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Create another keypair than the nominated manager
+    let other_keypair = Keypair::new();
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call accept manager instruction
+    let accept_manager_instruction = accept_manager_ix(&other_keypair.pubkey());
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[accept_manager_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &other_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::PendingManagerKeyMismatch as u32)
+        )
+    );
+}
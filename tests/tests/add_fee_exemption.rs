@@ -0,0 +1,167 @@
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+use escrow_program::{
+    errors::EscrowError,
+    instructions::{add_fee_exemption::AddFeeExemptionArgs, add_fee_exemption_ix},
+    state::{EscrowState, FeeExemption, ProgramAccount},
+};
+
+use crate::utils::{
+    add_escrow_state_account, add_wallet_account, get_program_account, prepare_program_test,
+};
+
+const DISCOUNT_BPS: u16 = 5_000;
+const EXPIRES_AT: i64 = 1_000_000_000_000;
+const PREFUNDED_LAMPORTS: u64 = 1; // NB: should be lower than the rent exemption
+
+#[tokio::test]
+async fn it_adds_fee_exemption() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, _) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 100, 500);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call add fee exemption instruction
+    let wallet_address = Pubkey::new_unique();
+    let add_fee_exemption_instruction = add_fee_exemption_ix(
+        &manager_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        AddFeeExemptionArgs {
+            wallet: wallet_address,
+            discount_bps: DISCOUNT_BPS,
+            expires_at: Some(EXPIRES_AT),
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[add_fee_exemption_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let (fee_exemption_address, _) =
+        FeeExemption::find_program_address(&escrow_program::ID, &wallet_address);
+    let fee_exemption =
+        get_program_account::<FeeExemption>(&banks_client, fee_exemption_address).await;
+    assert_eq!(fee_exemption.wallet, wallet_address);
+    assert_eq!(fee_exemption.discount_bps, DISCOUNT_BPS);
+    assert_eq!(fee_exemption.expires_at, Some(EXPIRES_AT));
+}
+
+#[tokio::test]
+async fn it_adds_fee_exemption_with_prefunded_address() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, _) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 100, 500);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Fund the fee exemption address of the wallet before its creation
+    let wallet_address = Pubkey::new_unique();
+    let (fee_exemption_address, _) =
+        FeeExemption::find_program_address(&escrow_program::ID, &wallet_address);
+    add_wallet_account(&mut program_test, fee_exemption_address, PREFUNDED_LAMPORTS);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call add fee exemption instruction
+    let add_fee_exemption_instruction = add_fee_exemption_ix(
+        &manager_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        AddFeeExemptionArgs {
+            wallet: wallet_address,
+            discount_bps: DISCOUNT_BPS,
+            expires_at: None,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[add_fee_exemption_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the funded lamports are topped up to the rent exemption
+    let fee_exemption_account = banks_client
+        .get_account(fee_exemption_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(fee_exemption_account.owner, escrow_program::ID);
+    assert_eq!(
+        fee_exemption_account.lamports,
+        Rent::default().minimum_balance(FeeExemption::LEN)
+    );
+
+    let fee_exemption =
+        get_program_account::<FeeExemption>(&banks_client, fee_exemption_address).await;
+    assert_eq!(fee_exemption.wallet, wallet_address);
+    assert_eq!(fee_exemption.discount_bps, DISCOUNT_BPS);
+}
+
+#[tokio::test]
+async fn it_fails_to_add_fee_exemption_by_non_manager() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let (escrow_state, _) = EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 100, 500);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call add fee exemption instruction, signed by a non-manager
+    let non_manager_keypair = Keypair::new();
+    let add_fee_exemption_instruction = add_fee_exemption_ix(
+        &non_manager_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        AddFeeExemptionArgs {
+            wallet: non_manager_keypair.pubkey(),
+            discount_bps: DISCOUNT_BPS,
+            expires_at: None,
+        },
+    );
+    let result = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[add_fee_exemption_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &non_manager_keypair],
+            last_blockhash,
+        ))
+        .await;
+
+    // Check the result
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::ManagerKeyUnauthorized as u32)
+        )
+    );
+}
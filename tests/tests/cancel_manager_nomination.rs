@@ -0,0 +1,41 @@
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+
+use escrow_program::{instructions::cancel_manager_nomination_ix, state::EscrowState};
+
+use crate::utils::{add_escrow_state_account, get_program_account, prepare_program_test};
+
+#[tokio::test]
+async fn it_cancels_manager_nomination() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account, with a nominated manager
+    let manager_keypair = Keypair::new();
+    let (mut escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    escrow_state.pending_manager = Some(Pubkey::new_unique());
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call cancel manager nomination instruction
+    let cancel_manager_nomination_instruction =
+        cancel_manager_nomination_ix(&manager_keypair.pubkey());
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[cancel_manager_nomination_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state_after_cancel =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+
+    assert_eq!(escrow_state_after_cancel.manager, manager_keypair.pubkey());
+    assert_eq!(escrow_state_after_cancel.pending_manager, None);
+}
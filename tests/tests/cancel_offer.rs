@@ -1,20 +1,38 @@
-use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
-use spl_associated_token_account::get_associated_token_address;
+use solana_sdk::{
+    instruction::InstructionError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
 use spl_token::state::Account as TokenAccount;
 
 use escrow_program::{
+    errors::EscrowError,
     instructions::cancel_offer_ix,
-    state::{EscrowState, Offer},
+    state::{EscrowState, Offer, ProgramAccount},
 };
 
 use crate::utils::{
-    add_escrow_state_account, add_offer_account, add_test_fixture_from_file, add_token_account,
+    add_escrow_state_account, add_mint_account_with_transfer_fee, add_native_token_account,
+    add_program_account, add_test_fixture_from_file, add_token_account,
+    add_token_account_with_transfer_fee_amount, add_wallet_account, get_token_balance,
     prepare_program_test,
 };
 
 const OFFER_ID: u64 = 0;
 const TOKEN_A_OFFERED: u64 = 69;
 const TOKEN_B_WANTED: u64 = 420;
+const TOKEN_2022_DECIMALS: u8 = 9;
+const TRANSFER_FEE_BPS: u16 = 100;
+const TOKEN_A_TRANSFER_FEE: u64 = 1; // NB: TOKEN_A_OFFERED * TRANSFER_FEE_BPS, rounded up
+const WALLET_LAMPORTS: u64 = 1_000_000_000;
+const MAKER_WSOL_BALANCE: u64 = 1_000;
 
 #[tokio::test]
 async fn it_cancels_offer() {
@@ -41,9 +59,11 @@ async fn it_cancels_offer() {
         maker_keypair.pubkey(),
         token_a_mint_address,
         token_b_mint_address,
+        TOKEN_A_OFFERED,
         TOKEN_B_WANTED,
+        maker_keypair.pubkey(),
     );
-    add_offer_account(&mut program_test, offer);
+    add_program_account(&mut program_test, offer_address, offer);
     let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
     add_token_account(
         &mut program_test,
@@ -60,6 +80,8 @@ async fn it_cancels_offer() {
     let cancel_offer_instruction = cancel_offer_ix(
         &maker_keypair.pubkey(),
         &token_a_mint_address,
+        &spl_token::ID,
+        &maker_keypair.pubkey(),
         &payer_keypair.pubkey(),
         OFFER_ID,
     );
@@ -85,3 +107,260 @@ async fn it_cancels_offer() {
 
     assert_eq!(maker_token_a_account_balance_after_cancel, TOKEN_A_OFFERED);
 }
+
+#[tokio::test]
+async fn it_cancels_offer_with_transfer_fee() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints (token A on Token-2022 with transfer fee, token B on SPL Token)
+    let token_a_mint_address = add_mint_account_with_transfer_fee(
+        &mut program_test,
+        TOKEN_2022_DECIMALS,
+        TRANSFER_FEE_BPS,
+    );
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0).0,
+    );
+
+    // Create maker keypair
+    let maker_keypair = Keypair::new();
+
+    // Initialize an offer (and its vault account) to be canceled
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_keypair.pubkey(),
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_keypair.pubkey(),
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address_with_program_id(
+        &offer_address,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_fee_amount(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+        0,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call cancel offer instruction
+    let cancel_offer_instruction = cancel_offer_ix(
+        &maker_keypair.pubkey(),
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+        &maker_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        OFFER_ID,
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[cancel_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the maker is refunded, net of the transfer fee
+    let maker_token_a_account_pubkey = get_associated_token_address_with_program_id(
+        &maker_keypair.pubkey(),
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, maker_token_a_account_pubkey).await,
+        TOKEN_A_OFFERED - TOKEN_A_TRANSFER_FEE
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_cancel_offer_with_other_rent_payer() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0).0,
+    );
+
+    // Create maker keypair, and pubkey of the original funder of the offer
+    let maker_keypair = Keypair::new();
+    let rent_payer_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be canceled
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_keypair.pubkey(),
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        rent_payer_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    add_token_account(
+        &mut program_test,
+        get_associated_token_address(&offer_address, &token_a_mint_address),
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call cancel offer instruction, refunding the rent to the maker instead
+    let cancel_offer_instruction = cancel_offer_ix(
+        &maker_keypair.pubkey(),
+        &token_a_mint_address,
+        &spl_token::ID,
+        &maker_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        OFFER_ID,
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[cancel_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::RentPayerKeyMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_cancels_offer_for_native_sol() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token B mint (token A is native SOL, refunded into the maker's wallet)
+    let token_a_mint_address = spl_token::native_mint::ID;
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0).0,
+    );
+
+    // Create maker keypair with SOL, and a funded token A token account (ATA) which should be
+    // left untouched, and pubkey of the original funder of the offer
+    let maker_keypair = Keypair::new();
+    add_wallet_account(&mut program_test, maker_keypair.pubkey(), WALLET_LAMPORTS);
+    let maker_token_a_account_pubkey =
+        get_associated_token_address(&maker_keypair.pubkey(), &token_a_mint_address);
+    add_native_token_account(
+        &mut program_test,
+        maker_token_a_account_pubkey,
+        maker_keypair.pubkey(),
+        MAKER_WSOL_BALANCE,
+    );
+    let rent_payer_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be canceled
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_keypair.pubkey(),
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        rent_payer_pubkey,
+    );
+    let offer_rent = Rent::default().minimum_balance(Offer::LEN);
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_native_token_account(
+        &mut program_test,
+        vault_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+    let vault_rent = Rent::default().minimum_balance(TokenAccount::LEN);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call cancel offer instruction
+    let cancel_offer_instruction = cancel_offer_ix(
+        &maker_keypair.pubkey(),
+        &token_a_mint_address,
+        &spl_token::ID,
+        &rent_payer_pubkey,
+        &payer_keypair.pubkey(),
+        OFFER_ID,
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[cancel_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the maker is refunded in SOL into its wallet
+    let maker_lamports_after_cancel = banks_client
+        .get_balance(maker_keypair.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        maker_lamports_after_cancel,
+        WALLET_LAMPORTS + TOKEN_A_OFFERED
+    );
+
+    let maker_token_a_balance_after_cancel =
+        get_token_balance(&banks_client, maker_token_a_account_pubkey).await;
+    assert_eq!(maker_token_a_balance_after_cancel, MAKER_WSOL_BALANCE);
+
+    let rent_payer_balance_after_cancel =
+        banks_client.get_balance(rent_payer_pubkey).await.unwrap();
+    assert_eq!(rent_payer_balance_after_cancel, offer_rent + vault_rent);
+
+    assert!(banks_client
+        .get_account(offer_address)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(banks_client
+        .get_account(vault_address)
+        .await
+        .unwrap()
+        .is_none());
+}
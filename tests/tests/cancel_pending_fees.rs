@@ -0,0 +1,58 @@
+use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction};
+
+use escrow_program::{
+    instructions::cancel_pending_fees_ix,
+    state::{EscrowState, FeeRates, FeeRounding, PendingFees},
+};
+
+use crate::utils::{add_escrow_state_account, get_program_account, prepare_program_test};
+
+const MAKER_FEE_BPS: u16 = 100;
+const TAKER_FEE_BPS: u16 = 500;
+
+#[tokio::test]
+async fn it_cancels_pending_fees() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account, with fees pending far in the future
+    let manager_keypair = Keypair::new();
+    let (mut escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        manager_keypair.pubkey(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    escrow_state.pending_fees = Some(PendingFees {
+        fee_rates: FeeRates {
+            maker_fee_bps: 0,
+            taker_fee_bps: 0,
+        },
+        effective_at: i64::MAX,
+        fee_rounding: FeeRounding::default(),
+    });
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call cancel pending fees instruction
+    let cancel_pending_fees_instruction = cancel_pending_fees_ix(&manager_keypair.pubkey());
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[cancel_pending_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state_after_cancel =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+
+    assert_eq!(escrow_state_after_cancel.pending_fees, None);
+    assert_eq!(escrow_state_after_cancel.maker_fee_bps, MAKER_FEE_BPS);
+    assert_eq!(escrow_state_after_cancel.taker_fee_bps, TAKER_FEE_BPS);
+}
@@ -0,0 +1,59 @@
+use solana_sdk::{
+    pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer, transaction::Transaction,
+};
+
+use escrow_program::{
+    instructions::close_fee_override_ix,
+    state::{EscrowState, FeeOverride, ProgramAccount},
+};
+
+use crate::utils::{add_escrow_state_account, add_program_account, prepare_program_test};
+
+#[tokio::test]
+async fn it_closes_fee_override() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, _) = EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize a fee override for a single mint
+    let mint_address = Pubkey::new_unique();
+    let (fee_override, fee_override_address) =
+        FeeOverride::new(&escrow_program::ID, mint_address, None, 0, 0);
+    add_program_account(&mut program_test, fee_override_address, fee_override);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call close fee override instruction
+    let close_fee_override_instruction =
+        close_fee_override_ix(&manager_keypair.pubkey(), &mint_address, None);
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[close_fee_override_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let fee_override_account = banks_client
+        .get_account(fee_override_address)
+        .await
+        .unwrap();
+    assert!(fee_override_account.is_none());
+
+    let manager_lamports = banks_client
+        .get_balance(manager_keypair.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        manager_lamports,
+        Rent::default().minimum_balance(FeeOverride::LEN)
+    );
+}
@@ -1,17 +1,30 @@
-use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction};
-use spl_associated_token_account::get_associated_token_address;
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
 use spl_token::state::Account as TokenAccount;
+use spl_token_2022::offchain::AccountFetchError;
 
 use escrow_program::{
-    instructions::{collect_fee::CollectFeeArgs, collect_fee_ix},
+    errors::EscrowError,
+    instructions::{collect_fee::CollectFeeArgs, collect_fee_ix, collect_fee_ix_with_extra_metas},
     state::EscrowState,
 };
 
 use crate::utils::{
-    add_escrow_state_account, add_test_fixture_from_file, add_token_account, prepare_program_test,
+    add_escrow_state_account, add_mint_account_with_transfer_hook, add_test_fixture_from_file,
+    add_token_account, add_token_account_with_transfer_hook_account, add_transfer_hook_program,
+    get_token_balance, prepare_program_test,
 };
 
 const ESCROW_FEE_BALANCE: u64 = 1337;
+const TOKEN_2022_DECIMALS: u8 = 9;
 
 #[tokio::test]
 async fn it_collects_fee() {
@@ -56,6 +69,7 @@ async fn it_collects_fee() {
     let collect_fee_instruction = collect_fee_ix(
         &manager_keypair.pubkey(),
         &token_mint_address,
+        &spl_token::ID,
         &destination_token_account_address,
         CollectFeeArgs {
             should_close_fee_account: false,
@@ -115,6 +129,7 @@ async fn it_collects_fee_and_closes_fee_account() {
     let collect_fee_instruction = collect_fee_ix(
         &manager_keypair.pubkey(),
         &token_mint_address,
+        &spl_token::ID,
         &destination_token_account_address,
         CollectFeeArgs {
             should_close_fee_account: true,
@@ -142,3 +157,245 @@ async fn it_collects_fee_and_closes_fee_account() {
         ESCROW_FEE_BALANCE,
     );
 }
+
+#[tokio::test]
+async fn it_collects_fee_by_fee_collector() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+
+    // Initialize the escrow state account, with a fee collector and a fee destination
+    let fee_collector_keypair = Keypair::new();
+    let treasury_pubkey = Pubkey::new_unique();
+    let (mut escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0);
+    escrow_state.fee_collector = Some(fee_collector_keypair.pubkey());
+    escrow_state.fee_destination = Some(treasury_pubkey);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize escrow fee account to be collected
+    let escrow_fee_account =
+        get_associated_token_address(&escrow_state_address, &token_mint_address);
+    add_token_account(
+        &mut program_test,
+        escrow_fee_account,
+        token_mint_address,
+        escrow_state_address,
+        ESCROW_FEE_BALANCE,
+    );
+
+    // Prepare and initialize treasury's token account for fee destination
+    let destination_token_account_address =
+        get_associated_token_address(&treasury_pubkey, &token_mint_address);
+    add_token_account(
+        &mut program_test,
+        destination_token_account_address,
+        token_mint_address,
+        treasury_pubkey,
+        0,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call collect fee instruction
+    let collect_fee_instruction = collect_fee_ix(
+        &fee_collector_keypair.pubkey(),
+        &token_mint_address,
+        &spl_token::ID,
+        &destination_token_account_address,
+        CollectFeeArgs {
+            should_close_fee_account: false,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[collect_fee_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &fee_collector_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let destination_balance_after_collect =
+        get_token_balance(&banks_client, destination_token_account_address).await;
+    assert_eq!(destination_balance_after_collect, ESCROW_FEE_BALANCE);
+}
+
+#[tokio::test]
+async fn it_collects_fee_with_transfer_hook() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+    add_transfer_hook_program(&mut program_test);
+
+    // Load token mint (on Token-2022 with transfer hook)
+    let token_mint_address =
+        add_mint_account_with_transfer_hook(&mut program_test, TOKEN_2022_DECIMALS);
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize escrow fee account to be collected
+    let escrow_fee_account = get_associated_token_address_with_program_id(
+        &escrow_state_address,
+        &token_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_hook_account(
+        &mut program_test,
+        escrow_fee_account,
+        token_mint_address,
+        escrow_state_address,
+        ESCROW_FEE_BALANCE,
+    );
+
+    // Prepare and initialize manager's token account for fee destination
+    let destination_token_account_address = get_associated_token_address_with_program_id(
+        &manager_keypair.pubkey(),
+        &token_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_hook_account(
+        &mut program_test,
+        destination_token_account_address,
+        token_mint_address,
+        manager_keypair.pubkey(),
+        0,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call collect fee instruction without the transfer hook accounts
+    let collect_fee_instruction = collect_fee_ix(
+        &manager_keypair.pubkey(),
+        &token_mint_address,
+        &spl_token_2022::ID,
+        &destination_token_account_address,
+        CollectFeeArgs {
+            should_close_fee_account: false,
+        },
+    );
+    let result = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[collect_fee_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await;
+    assert!(result.is_err());
+
+    // Call collect fee instruction with the transfer hook accounts resolved
+    let collect_fee_instruction = collect_fee_ix_with_extra_metas(
+        &manager_keypair.pubkey(),
+        &token_mint_address,
+        &spl_token_2022::ID,
+        &destination_token_account_address,
+        CollectFeeArgs {
+            should_close_fee_account: false,
+        },
+        |address| {
+            let banks_client = banks_client.clone();
+            async move {
+                let account = banks_client.get_account(address).await?;
+                Ok::<_, AccountFetchError>(account.map(|account| account.data))
+            }
+        },
+    )
+    .await
+    .unwrap();
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[collect_fee_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        get_token_balance(&banks_client, destination_token_account_address).await,
+        ESCROW_FEE_BALANCE
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_collect_fee_to_other_than_fee_destination() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+
+    // Initialize the escrow state account, with a fee destination
+    let manager_keypair = Keypair::new();
+    let (mut escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    escrow_state.fee_destination = Some(Pubkey::new_unique());
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize escrow fee account to be collected
+    let escrow_fee_account =
+        get_associated_token_address(&escrow_state_address, &token_mint_address);
+    add_token_account(
+        &mut program_test,
+        escrow_fee_account,
+        token_mint_address,
+        escrow_state_address,
+        ESCROW_FEE_BALANCE,
+    );
+
+    // Prepare and initialize manager's token account for fee destination
+    let destination_token_account_address =
+        get_associated_token_address(&manager_keypair.pubkey(), &token_mint_address);
+    add_token_account(
+        &mut program_test,
+        destination_token_account_address,
+        token_mint_address,
+        manager_keypair.pubkey(),
+        0,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call collect fee instruction, to the manager's token account
+    let collect_fee_instruction = collect_fee_ix(
+        &manager_keypair.pubkey(),
+        &token_mint_address,
+        &spl_token::ID,
+        &destination_token_account_address,
+        CollectFeeArgs {
+            should_close_fee_account: false,
+        },
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[collect_fee_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::TokenAccountMismatch as u32)
+        )
+    );
+}
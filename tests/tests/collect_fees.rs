@@ -0,0 +1,323 @@
+use solana_sdk::{
+    instruction::InstructionError,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use escrow_program::{
+    errors::EscrowError,
+    instructions::{
+        collect_fees::CollectFeesArgs, collect_fees_ix, collect_fees_ixs, MAX_COLLECT_FEES_MINTS,
+    },
+    state::EscrowState,
+};
+
+use crate::utils::{
+    add_escrow_state_account, add_mint_account, add_mint_account_with_transfer_hook,
+    add_test_fixture_from_file, add_token_account_with_program_id,
+    add_token_account_with_transfer_hook_account, add_transfer_hook_program, get_token_balance,
+    prepare_program_test,
+};
+
+const ESCROW_FEE_BALANCE: u64 = 1337;
+const TOKEN_2022_DECIMALS: u8 = 9;
+
+#[tokio::test]
+async fn it_collects_fees_and_closes_fee_accounts() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints, of both token programs
+    let mints = [
+        (
+            add_test_fixture_from_file(&mut program_test, "inf-mint.json"),
+            spl_token::ID,
+        ),
+        (
+            add_test_fixture_from_file(&mut program_test, "usdc-mint.json"),
+            spl_token::ID,
+        ),
+        (
+            add_mint_account(&mut program_test, TOKEN_2022_DECIMALS, spl_token_2022::ID),
+            spl_token_2022::ID,
+        ),
+    ];
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize escrow fee accounts to be collected, and manager's token accounts for fee
+    // destination
+    for (index, (mint_address, token_program_id)) in mints.iter().enumerate() {
+        add_token_account_with_program_id(
+            &mut program_test,
+            get_associated_token_address_with_program_id(
+                &escrow_state_address,
+                mint_address,
+                token_program_id,
+            ),
+            *mint_address,
+            escrow_state_address,
+            ESCROW_FEE_BALANCE + index as u64,
+            *token_program_id,
+        );
+        add_token_account_with_program_id(
+            &mut program_test,
+            get_associated_token_address_with_program_id(
+                &manager_keypair.pubkey(),
+                mint_address,
+                token_program_id,
+            ),
+            *mint_address,
+            manager_keypair.pubkey(),
+            0,
+            *token_program_id,
+        );
+    }
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call collect fees instruction
+    let collect_fees_instruction = collect_fees_ix(
+        &manager_keypair.pubkey(),
+        &manager_keypair.pubkey(),
+        &mints,
+        CollectFeesArgs {
+            should_close_fee_accounts: true,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[collect_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    for (index, (mint_address, token_program_id)) in mints.iter().enumerate() {
+        let destination_token_account_balance_after_collect = get_token_balance(
+            &banks_client,
+            get_associated_token_address_with_program_id(
+                &manager_keypair.pubkey(),
+                mint_address,
+                token_program_id,
+            ),
+        )
+        .await;
+        assert_eq!(
+            destination_token_account_balance_after_collect,
+            ESCROW_FEE_BALANCE + index as u64
+        );
+
+        let escrow_fee_account = banks_client
+            .get_account(get_associated_token_address_with_program_id(
+                &escrow_state_address,
+                mint_address,
+                token_program_id,
+            ))
+            .await
+            .unwrap();
+        assert!(escrow_fee_account.is_none());
+    }
+}
+
+#[tokio::test]
+async fn it_fails_to_collect_fees_with_incomplete_accounts() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, _) = EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call collect fees instruction, without the destination of the mint
+    let mut collect_fees_instruction = collect_fees_ix(
+        &manager_keypair.pubkey(),
+        &manager_keypair.pubkey(),
+        &[(token_mint_address, spl_token::ID)],
+        CollectFeesArgs {
+            should_close_fee_accounts: false,
+        },
+    );
+    collect_fees_instruction.accounts.pop();
+    let result = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[collect_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await;
+
+    // Check the result
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_collect_fees_with_other_token_2022_program() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mint (on Token-2022)
+    let token_mint_address =
+        add_mint_account(&mut program_test, TOKEN_2022_DECIMALS, spl_token_2022::ID);
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, _) = EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call collect fees instruction, with SPL Token in place of Token-2022
+    let mut collect_fees_instruction = collect_fees_ix(
+        &manager_keypair.pubkey(),
+        &manager_keypair.pubkey(),
+        &[(token_mint_address, spl_token_2022::ID)],
+        CollectFeesArgs {
+            should_close_fee_accounts: false,
+        },
+    );
+    collect_fees_instruction.accounts[3].pubkey = spl_token::ID;
+    let result = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[collect_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await;
+
+    // Check the result
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::TokenProgramMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_collect_fees_of_mint_with_transfer_hook() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+    add_transfer_hook_program(&mut program_test);
+
+    // Load token mint (on Token-2022 with transfer hook)
+    let token_mint_address =
+        add_mint_account_with_transfer_hook(&mut program_test, TOKEN_2022_DECIMALS);
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize escrow fee account to be collected, and destination token account
+    let escrow_fee_account = get_associated_token_address_with_program_id(
+        &escrow_state_address,
+        &token_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_hook_account(
+        &mut program_test,
+        escrow_fee_account,
+        token_mint_address,
+        escrow_state_address,
+        ESCROW_FEE_BALANCE,
+    );
+    let destination_token_account_address = get_associated_token_address_with_program_id(
+        &manager_keypair.pubkey(),
+        &token_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_hook_account(
+        &mut program_test,
+        destination_token_account_address,
+        token_mint_address,
+        manager_keypair.pubkey(),
+        0,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call collect fees instruction
+    let collect_fees_instruction = collect_fees_ix(
+        &manager_keypair.pubkey(),
+        &manager_keypair.pubkey(),
+        &[(token_mint_address, spl_token_2022::ID)],
+        CollectFeesArgs {
+            should_close_fee_accounts: false,
+        },
+    );
+    let result = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[collect_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await;
+
+    // Check the result
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::TransferHookNotSupported as u32)
+        )
+    );
+}
+
+#[test]
+fn it_chunks_collect_fees_instructions_to_fit_transactions() {
+    let payer_pubkey = Pubkey::new_unique();
+    let authority_pubkey = Pubkey::new_unique();
+    let mints = (0..2 * MAX_COLLECT_FEES_MINTS + 1)
+        .map(|_| (Pubkey::new_unique(), spl_token_2022::ID))
+        .collect::<Vec<_>>();
+
+    let collect_fees_instructions = collect_fees_ixs(
+        &authority_pubkey,
+        &Pubkey::new_unique(),
+        &mints,
+        CollectFeesArgs {
+            should_close_fee_accounts: true,
+        },
+    );
+    assert_eq!(collect_fees_instructions.len(), 3);
+    assert_eq!(
+        collect_fees_instructions[2].accounts.len(),
+        collect_fees_instructions[0].accounts.len() - 3 * (MAX_COLLECT_FEES_MINTS - 1)
+    );
+
+    // Signed by the payer and the authority (compact-u16 length prefix, 64 bytes each)
+    let transaction =
+        Transaction::new_with_payer(&collect_fees_instructions[..1], Some(&payer_pubkey));
+    let transaction_size = 1 + 2 * 64 + transaction.message.serialize().len();
+    assert!(transaction_size <= PACKET_DATA_SIZE);
+}
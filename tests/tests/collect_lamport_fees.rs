@@ -0,0 +1,126 @@
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+
+use escrow_program::{
+    errors::EscrowError,
+    instructions::collect_lamport_fees_ix,
+    state::{EscrowState, LamportFeeVault, ProgramAccount},
+};
+
+use crate::utils::{
+    add_escrow_state_account, add_program_account, add_wallet_account, prepare_program_test,
+};
+
+const FEE_LAMPORTS: u64 = 1337;
+const WALLET_LAMPORTS: u64 = 1_000_000_000;
+
+#[tokio::test]
+async fn it_collects_lamport_fees() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, _) = EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize the lamport fee vault
+    let (lamport_fee_vault, lamport_fee_vault_address) = LamportFeeVault::new(&escrow_program::ID);
+    add_program_account(
+        &mut program_test,
+        lamport_fee_vault_address,
+        lamport_fee_vault,
+    );
+
+    let destination_address = Pubkey::new_unique();
+    add_wallet_account(&mut program_test, destination_address, WALLET_LAMPORTS);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call collect lamport fees instruction, after paying fees into the vault
+    let pay_lamport_fees_instruction = system_instruction::transfer(
+        &payer_keypair.pubkey(),
+        &lamport_fee_vault_address,
+        FEE_LAMPORTS,
+    );
+    let collect_lamport_fees_instruction =
+        collect_lamport_fees_ix(&manager_keypair.pubkey(), &destination_address);
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                pay_lamport_fees_instruction,
+                collect_lamport_fees_instruction,
+            ],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the vault keeps its rent
+    let destination_lamports = banks_client.get_balance(destination_address).await.unwrap();
+    assert_eq!(destination_lamports, WALLET_LAMPORTS + FEE_LAMPORTS);
+
+    let lamport_fee_vault_lamports = banks_client
+        .get_balance(lamport_fee_vault_address)
+        .await
+        .unwrap();
+    assert_eq!(
+        lamport_fee_vault_lamports,
+        Rent::default().minimum_balance(LamportFeeVault::LEN)
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_collect_lamport_fees_to_other_than_fee_destination() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account, with a pinned fee destination
+    let manager_keypair = Keypair::new();
+    let (mut escrow_state, _) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    escrow_state.fee_destination = Some(Pubkey::new_unique());
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize the lamport fee vault
+    let (lamport_fee_vault, lamport_fee_vault_address) = LamportFeeVault::new(&escrow_program::ID);
+    add_program_account(
+        &mut program_test,
+        lamport_fee_vault_address,
+        lamport_fee_vault,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call collect lamport fees instruction, to the manager instead of the fee destination
+    let collect_lamport_fees_instruction =
+        collect_lamport_fees_ix(&manager_keypair.pubkey(), &manager_keypair.pubkey());
+    let result = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[collect_lamport_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await;
+
+    // Check the result
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::FeeDestinationKeyMismatch as u32)
+        )
+    );
+}
@@ -0,0 +1,156 @@
+use solana_sdk::{
+    pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer, transaction::Transaction,
+};
+
+use escrow_program::{
+    instructions::{create_fee_override::CreateFeeOverrideArgs, create_fee_override_ix},
+    state::{EscrowState, FeeOverride, ProgramAccount},
+};
+
+use crate::utils::{
+    add_escrow_state_account, add_wallet_account, get_program_account, prepare_program_test,
+};
+
+const MAKER_FEE_BPS: u16 = 100;
+const TAKER_FEE_BPS: u16 = 500;
+const OVERRIDE_MAKER_FEE_BPS: u16 = 10;
+const OVERRIDE_TAKER_FEE_BPS: u16 = 0;
+const PREFUNDED_LAMPORTS: u64 = 1; // NB: should be lower than the rent exemption
+
+#[tokio::test]
+async fn it_creates_fee_override() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, _) = EscrowState::new(
+        &escrow_program::ID,
+        manager_keypair.pubkey(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call create fee override instruction, for a mint pair and for a single mint
+    let token_a_mint_address = Pubkey::new_unique();
+    let token_b_mint_address = Pubkey::new_unique();
+    let create_pair_fee_override_instruction = create_fee_override_ix(
+        &manager_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        CreateFeeOverrideArgs {
+            mint: token_a_mint_address,
+            paired_mint: Some(token_b_mint_address),
+            maker_fee_bps: OVERRIDE_MAKER_FEE_BPS,
+            taker_fee_bps: OVERRIDE_TAKER_FEE_BPS,
+        },
+    );
+    let create_mint_fee_override_instruction = create_fee_override_ix(
+        &manager_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        CreateFeeOverrideArgs {
+            mint: token_a_mint_address,
+            paired_mint: None,
+            maker_fee_bps: OVERRIDE_MAKER_FEE_BPS,
+            taker_fee_bps: OVERRIDE_TAKER_FEE_BPS,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[
+                create_pair_fee_override_instruction,
+                create_mint_fee_override_instruction,
+            ],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let (pair_fee_override_address, _) = FeeOverride::find_program_address(
+        &escrow_program::ID,
+        &token_a_mint_address,
+        Some(&token_b_mint_address),
+    );
+    let pair_fee_override =
+        get_program_account::<FeeOverride>(&banks_client, pair_fee_override_address).await;
+    assert_eq!(pair_fee_override.mint, token_a_mint_address);
+    assert_eq!(pair_fee_override.paired_mint, Some(token_b_mint_address));
+    assert_eq!(pair_fee_override.maker_fee_bps, OVERRIDE_MAKER_FEE_BPS);
+    assert_eq!(pair_fee_override.taker_fee_bps, OVERRIDE_TAKER_FEE_BPS);
+
+    let (mint_fee_override_address, _) =
+        FeeOverride::find_program_address(&escrow_program::ID, &token_a_mint_address, None);
+    let mint_fee_override =
+        get_program_account::<FeeOverride>(&banks_client, mint_fee_override_address).await;
+    assert_eq!(mint_fee_override.mint, token_a_mint_address);
+    assert_eq!(mint_fee_override.paired_mint, None);
+}
+
+#[tokio::test]
+async fn it_creates_fee_override_with_prefunded_address() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, _) = EscrowState::new(
+        &escrow_program::ID,
+        manager_keypair.pubkey(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Fund the fee override address before its creation
+    let token_a_mint_address = Pubkey::new_unique();
+    let (fee_override_address, _) =
+        FeeOverride::find_program_address(&escrow_program::ID, &token_a_mint_address, None);
+    add_wallet_account(&mut program_test, fee_override_address, PREFUNDED_LAMPORTS);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call create fee override instruction
+    let create_fee_override_instruction = create_fee_override_ix(
+        &manager_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        CreateFeeOverrideArgs {
+            mint: token_a_mint_address,
+            paired_mint: None,
+            maker_fee_bps: OVERRIDE_MAKER_FEE_BPS,
+            taker_fee_bps: OVERRIDE_TAKER_FEE_BPS,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[create_fee_override_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the funded lamports are topped up to the rent exemption
+    let fee_override_account = banks_client
+        .get_account(fee_override_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(fee_override_account.owner, escrow_program::ID);
+    assert_eq!(
+        fee_override_account.lamports,
+        Rent::default().minimum_balance(FeeOverride::LEN)
+    );
+
+    let fee_override =
+        get_program_account::<FeeOverride>(&banks_client, fee_override_address).await;
+    assert_eq!(fee_override.mint, token_a_mint_address);
+    assert_eq!(fee_override.maker_fee_bps, OVERRIDE_MAKER_FEE_BPS);
+}
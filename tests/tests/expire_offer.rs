@@ -0,0 +1,480 @@
+use solana_sdk::{
+    instruction::InstructionError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
+use spl_token::state::Account as TokenAccount;
+use spl_token_2022::offchain::AccountFetchError;
+
+use escrow_program::{
+    errors::EscrowError,
+    instructions::{expire_offer_ix, expire_offer_ix_with_extra_metas},
+    state::{EscrowState, Offer, ProgramAccount},
+};
+
+use crate::utils::{
+    add_escrow_state_account, add_mint_account_with_transfer_hook, add_native_token_account,
+    add_program_account, add_test_fixture_from_file, add_token_account,
+    add_token_account_with_transfer_hook_account, add_transfer_hook_program, add_wallet_account,
+    get_token_balance, prepare_program_test,
+};
+
+const OFFER_ID: u64 = 0;
+const TOKEN_A_OFFERED: u64 = 69;
+const TOKEN_B_WANTED: u64 = 420;
+const EXPIRES_AT: i64 = 1; // NB: should be in the past
+const NOT_EXPIRES_AT: i64 = i64::MAX; // NB: should be in the future
+const TOKEN_2022_DECIMALS: u8 = 9;
+const WALLET_LAMPORTS: u64 = 1_000_000_000;
+
+#[tokio::test]
+async fn it_expires_offer() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0).0,
+    );
+
+    // Create maker pubkey, and pubkey of the original funder of the offer
+    let maker_pubkey = Pubkey::new_unique();
+    let rent_payer_pubkey = Pubkey::new_unique();
+
+    // Initialize an expired offer (and its vault account)
+    let (mut offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        rent_payer_pubkey,
+    );
+    offer.expires_at = Some(EXPIRES_AT);
+    let offer_rent = Rent::default().minimum_balance(Offer::LEN);
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+    let vault_rent = Rent::default().minimum_balance(TokenAccount::LEN);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call expire offer instruction (signed by a third party)
+    let expire_offer_instruction = expire_offer_ix(
+        &maker_pubkey,
+        &token_a_mint_address,
+        &spl_token::ID,
+        &rent_payer_pubkey,
+        &payer_keypair.pubkey(),
+        OFFER_ID,
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[expire_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let maker_token_a_account_pubkey =
+        get_associated_token_address(&maker_pubkey, &token_a_mint_address);
+    let maker_token_a_account_balance_after_expire = banks_client
+        .get_packed_account_data::<TokenAccount>(maker_token_a_account_pubkey)
+        .await
+        .unwrap()
+        .amount;
+    assert_eq!(maker_token_a_account_balance_after_expire, TOKEN_A_OFFERED);
+
+    let rent_payer_balance_after_expire =
+        banks_client.get_balance(rent_payer_pubkey).await.unwrap();
+    assert_eq!(rent_payer_balance_after_expire, offer_rent + vault_rent);
+
+    assert!(banks_client
+        .get_account(offer_address)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn it_expires_offer_with_transfer_hook() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+    add_transfer_hook_program(&mut program_test);
+
+    // Load token mints (token A on Token-2022 with transfer hook, token B on SPL Token)
+    let token_a_mint_address =
+        add_mint_account_with_transfer_hook(&mut program_test, TOKEN_2022_DECIMALS);
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0).0,
+    );
+
+    // Create maker pubkey, and pubkey of the original funder of the offer
+    let maker_pubkey = Pubkey::new_unique();
+    let rent_payer_pubkey = Pubkey::new_unique();
+
+    // Initialize an expired offer (and its vault account)
+    let (mut offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        rent_payer_pubkey,
+    );
+    offer.expires_at = Some(EXPIRES_AT);
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address_with_program_id(
+        &offer_address,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_hook_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call expire offer instruction without the transfer hook accounts
+    let expire_offer_instruction = expire_offer_ix(
+        &maker_pubkey,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+        &rent_payer_pubkey,
+        &payer_keypair.pubkey(),
+        OFFER_ID,
+    );
+    let result = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[expire_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair],
+            last_blockhash,
+        ))
+        .await;
+    assert!(result.is_err());
+
+    // Call expire offer instruction with the transfer hook accounts resolved
+    let expire_offer_instruction = expire_offer_ix_with_extra_metas(
+        &maker_pubkey,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+        &rent_payer_pubkey,
+        &payer_keypair.pubkey(),
+        OFFER_ID,
+        |address| {
+            let banks_client = banks_client.clone();
+            async move {
+                let account = banks_client.get_account(address).await?;
+                Ok::<_, AccountFetchError>(account.map(|account| account.data))
+            }
+        },
+    )
+    .await
+    .unwrap();
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[expire_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let maker_token_a_account_pubkey = get_associated_token_address_with_program_id(
+        &maker_pubkey,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, maker_token_a_account_pubkey).await,
+        TOKEN_A_OFFERED
+    );
+
+    assert!(banks_client
+        .get_account(vault_address)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn it_expires_offer_for_native_sol() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token B mint (token A is native SOL, unwrapped into the maker's wallet)
+    let token_a_mint_address = spl_token::native_mint::ID;
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0).0,
+    );
+
+    // Create maker wallet, and pubkey of the original funder of the offer
+    let maker_pubkey = Pubkey::new_unique();
+    add_wallet_account(&mut program_test, maker_pubkey, WALLET_LAMPORTS);
+    let rent_payer_pubkey = Pubkey::new_unique();
+
+    // Initialize an expired offer (and its vault account)
+    let (mut offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        rent_payer_pubkey,
+    );
+    offer.expires_at = Some(EXPIRES_AT);
+    let offer_rent = Rent::default().minimum_balance(Offer::LEN);
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_native_token_account(
+        &mut program_test,
+        vault_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+    let vault_rent = Rent::default().minimum_balance(TokenAccount::LEN);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call expire offer instruction (signed by a third party)
+    let expire_offer_instruction = expire_offer_ix(
+        &maker_pubkey,
+        &token_a_mint_address,
+        &spl_token::ID,
+        &rent_payer_pubkey,
+        &payer_keypair.pubkey(),
+        OFFER_ID,
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[expire_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the maker is refunded in SOL without any token A token account
+    let maker_lamports_after_expire = banks_client.get_balance(maker_pubkey).await.unwrap();
+    assert_eq!(
+        maker_lamports_after_expire,
+        WALLET_LAMPORTS + TOKEN_A_OFFERED
+    );
+
+    let maker_token_a_account_pubkey =
+        get_associated_token_address(&maker_pubkey, &token_a_mint_address);
+    assert!(banks_client
+        .get_account(maker_token_a_account_pubkey)
+        .await
+        .unwrap()
+        .is_none());
+
+    let rent_payer_balance_after_expire =
+        banks_client.get_balance(rent_payer_pubkey).await.unwrap();
+    assert_eq!(rent_payer_balance_after_expire, offer_rent + vault_rent);
+
+    assert!(banks_client
+        .get_account(offer_address)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(banks_client
+        .get_account(vault_address)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn it_fails_to_expire_unexpired_offer() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0).0,
+    );
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer without expiry, and an offer expiring in the future (and their vaults)
+    let offer_expiries = [(OFFER_ID, None), (OFFER_ID + 1, Some(NOT_EXPIRES_AT))];
+    for (offer_id, expires_at) in offer_expiries {
+        let (mut offer, offer_address) = Offer::new(
+            &escrow_program::ID,
+            offer_id,
+            maker_pubkey,
+            token_a_mint_address,
+            token_b_mint_address,
+            TOKEN_A_OFFERED,
+            TOKEN_B_WANTED,
+            maker_pubkey,
+        );
+        offer.expires_at = expires_at;
+        add_program_account(&mut program_test, offer_address, offer);
+        add_token_account(
+            &mut program_test,
+            get_associated_token_address(&offer_address, &token_a_mint_address),
+            token_a_mint_address,
+            offer_address,
+            TOKEN_A_OFFERED,
+        );
+    }
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    for (offer_id, _) in offer_expiries {
+        // Call expire offer instruction (signed by a third party)
+        let expire_offer_instruction = expire_offer_ix(
+            &maker_pubkey,
+            &token_a_mint_address,
+            &spl_token::ID,
+            &maker_pubkey,
+            &payer_keypair.pubkey(),
+            offer_id,
+        );
+        let err = banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[expire_offer_instruction],
+                Some(&payer_keypair.pubkey()),
+                &[&payer_keypair],
+                last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap();
+
+        // Check the result
+        assert_eq!(
+            err,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(EscrowError::OfferNotExpired as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn it_fails_to_expire_offer_with_other_rent_payer() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0).0,
+    );
+
+    // Create maker pubkey, and pubkey of the original funder of the offer
+    let maker_pubkey = Pubkey::new_unique();
+    let rent_payer_pubkey = Pubkey::new_unique();
+
+    // Initialize an expired offer (and its vault account)
+    let (mut offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        rent_payer_pubkey,
+    );
+    offer.expires_at = Some(EXPIRES_AT);
+    add_program_account(&mut program_test, offer_address, offer);
+    add_token_account(
+        &mut program_test,
+        get_associated_token_address(&offer_address, &token_a_mint_address),
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call expire offer instruction, refunding the rent to the third party instead
+    let expire_offer_instruction = expire_offer_ix(
+        &maker_pubkey,
+        &token_a_mint_address,
+        &spl_token::ID,
+        &payer_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        OFFER_ID,
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[expire_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::RentPayerKeyMismatch as u32)
+        )
+    );
+}
@@ -6,7 +6,7 @@ use escrow_program::{
     state::EscrowState,
 };
 
-use crate::utils::prepare_program_test;
+use crate::utils::{get_program_account, prepare_program_test};
 
 #[tokio::test]
 async fn it_initializes() {
@@ -41,10 +41,8 @@ async fn it_initializes() {
     // Check the result
     let (escrow_state_address, _bump) = EscrowState::find_program_address(&escrow_program::ID);
 
-    let escrow_state_after_init = banks_client
-        .get_account_data_with_borsh::<EscrowState>(escrow_state_address)
-        .await
-        .unwrap();
+    let escrow_state_after_init =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
 
     assert_eq!(
         escrow_state_after_init.manager,
@@ -1,14 +1,31 @@
-use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
-use spl_associated_token_account::get_associated_token_address;
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
 use spl_token::state::Account as TokenAccount;
+use spl_token_2022::offchain::AccountFetchError;
 
 use escrow_program::{
-    instructions::{make_offer::MakeOfferArgs, make_offer_ix},
-    state::{EscrowState, Offer},
+    errors::EscrowError,
+    instructions::{make_offer::MakeOfferArgs, make_offer_ix, make_offer_ix_with_extra_metas},
+    state::{
+        EscrowState, FeeExemption, FeeRates, FeeRounding, LamportFeeVault, Offer, ProgramAccount,
+    },
 };
 
 use crate::utils::{
-    add_escrow_state_account, add_test_fixture_from_file, add_token_account, prepare_program_test,
+    add_escrow_state_account, add_mint_account_with_transfer_fee,
+    add_mint_account_with_transfer_hook, add_program_account, add_test_fixture_from_file,
+    add_token_account, add_token_account_with_transfer_fee_amount,
+    add_token_account_with_transfer_hook_account, add_transfer_hook_program, add_wallet_account,
+    get_program_account, get_token_balance, prepare_program_test,
 };
 
 const OFFER_ID: u64 = 0;
@@ -17,6 +34,12 @@ const TOKEN_A_OFFERED: u64 = 69; // NB: should be lower that MAKER_TOKEN_A_BALAN
 const TOKEN_B_WANTED: u64 = 420;
 const MAKER_FEE_BPS: u16 = 100;
 const TAKER_FEE_BPS: u16 = 500;
+const TOKEN_2022_DECIMALS: u8 = 9;
+const TRANSFER_FEE_BPS: u16 = 100;
+const TOKEN_A_TRANSFER_FEE: u64 = 1; // NB: TOKEN_A_OFFERED * TRANSFER_FEE_BPS, rounded up
+const MAKER_LAMPORTS: u64 = 1_000_000_000;
+const MAKER_FEE_DISCOUNT_BPS: u16 = 2_500;
+const MAKE_LAMPORT_FEE: u64 = 5_000;
 
 #[tokio::test]
 async fn it_makes_offer() {
@@ -62,11 +85,16 @@ async fn it_makes_offer() {
         &maker_token_a_account_pubkey,
         &token_a_mint_address,
         &token_b_mint_address,
+        &spl_token::ID,
         &payer_keypair.pubkey(),
         MakeOfferArgs {
             id: OFFER_ID,
             token_a_offered_amount: TOKEN_A_OFFERED,
             token_b_wanted_amount: TOKEN_B_WANTED,
+            expires_at: None,
+            allowed_taker: None,
+            taker_allowlist_root: None,
+            has_fee_exemption: false,
         },
     );
     banks_client
@@ -82,15 +110,22 @@ async fn it_makes_offer() {
     // Check the result
     let (offer_address, _bump) =
         Offer::find_program_address(&escrow_program::ID, &maker_keypair.pubkey(), OFFER_ID);
-    let offer_after_make = banks_client
-        .get_account_data_with_borsh::<Offer>(offer_address)
-        .await
-        .unwrap();
+    let offer_after_make = get_program_account::<Offer>(&banks_client, offer_address).await;
     assert_eq!(offer_after_make.id, OFFER_ID);
     assert_eq!(offer_after_make.maker, maker_keypair.pubkey());
     assert_eq!(offer_after_make.token_a_mint, token_a_mint_address);
     assert_eq!(offer_after_make.token_b_mint, token_b_mint_address);
+    assert_eq!(offer_after_make.token_a_offered_amount, TOKEN_A_OFFERED);
     assert_eq!(offer_after_make.token_b_wanted_amount, TOKEN_B_WANTED);
+    assert_eq!(offer_after_make.maker_fee_discount_bps, 0);
+    assert_eq!(
+        offer_after_make.fee_rates,
+        Some(FeeRates {
+            maker_fee_bps: MAKER_FEE_BPS,
+            taker_fee_bps: TAKER_FEE_BPS,
+        })
+    );
+    assert_eq!(offer_after_make.fee_rounding, Some(FeeRounding::default()));
 
     let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
     let vault_balance_after_make = banks_client
@@ -100,3 +135,526 @@ async fn it_makes_offer() {
         .amount;
     assert_eq!(vault_balance_after_make, TOKEN_A_OFFERED);
 }
+
+#[tokio::test]
+async fn it_makes_offer_with_fee_exemption() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(
+            &escrow_program::ID,
+            Pubkey::new_unique(),
+            MAKER_FEE_BPS,
+            TAKER_FEE_BPS,
+        )
+        .0,
+    );
+
+    // Create maker keypair, and token A token account address (ATA)
+    let maker_keypair = Keypair::new();
+    let maker_token_a_account_pubkey =
+        get_associated_token_address(&maker_keypair.pubkey(), &token_a_mint_address);
+
+    // Initialize maker's token A ATA
+    add_token_account(
+        &mut program_test,
+        maker_token_a_account_pubkey,
+        token_a_mint_address,
+        maker_keypair.pubkey(),
+        MAKER_TOKEN_A_BALANCE,
+    );
+
+    // Initialize a fee exemption of the maker
+    let (fee_exemption, fee_exemption_address) = FeeExemption::new(
+        &escrow_program::ID,
+        maker_keypair.pubkey(),
+        MAKER_FEE_DISCOUNT_BPS,
+        None,
+    );
+    add_program_account(&mut program_test, fee_exemption_address, fee_exemption);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call make offer instruction with the fee exemption of the maker
+    let make_offer_instruction = make_offer_ix(
+        &maker_keypair.pubkey(),
+        &maker_token_a_account_pubkey,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &payer_keypair.pubkey(),
+        MakeOfferArgs {
+            id: OFFER_ID,
+            token_a_offered_amount: TOKEN_A_OFFERED,
+            token_b_wanted_amount: TOKEN_B_WANTED,
+            expires_at: None,
+            allowed_taker: None,
+            taker_allowlist_root: None,
+            has_fee_exemption: true,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[make_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the discount of the maker is recorded on the offer
+    let (offer_address, _bump) =
+        Offer::find_program_address(&escrow_program::ID, &maker_keypair.pubkey(), OFFER_ID);
+    let offer_after_make = get_program_account::<Offer>(&banks_client, offer_address).await;
+    assert_eq!(
+        offer_after_make.maker_fee_discount_bps,
+        MAKER_FEE_DISCOUNT_BPS
+    );
+}
+
+#[tokio::test]
+async fn it_makes_offer_with_lamport_fee() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account, with a make lamport fee
+    let (mut escrow_state, _) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    escrow_state.make_lamport_fee = MAKE_LAMPORT_FEE;
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize the lamport fee vault
+    let (lamport_fee_vault, lamport_fee_vault_address) = LamportFeeVault::new(&escrow_program::ID);
+    add_program_account(
+        &mut program_test,
+        lamport_fee_vault_address,
+        lamport_fee_vault,
+    );
+
+    // Create maker keypair, and token A token account address (ATA)
+    let maker_keypair = Keypair::new();
+    let maker_token_a_account_pubkey =
+        get_associated_token_address(&maker_keypair.pubkey(), &token_a_mint_address);
+
+    // Initialize maker's token A ATA
+    add_token_account(
+        &mut program_test,
+        maker_token_a_account_pubkey,
+        token_a_mint_address,
+        maker_keypair.pubkey(),
+        MAKER_TOKEN_A_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call make offer instruction, paying the make lamport fee
+    let make_offer_instruction = make_offer_ix(
+        &maker_keypair.pubkey(),
+        &maker_token_a_account_pubkey,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &payer_keypair.pubkey(),
+        MakeOfferArgs {
+            id: OFFER_ID,
+            token_a_offered_amount: TOKEN_A_OFFERED,
+            token_b_wanted_amount: TOKEN_B_WANTED,
+            expires_at: None,
+            allowed_taker: None,
+            taker_allowlist_root: None,
+            has_fee_exemption: false,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[make_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the funding account paid the make lamport fee into the vault
+    let lamport_fee_vault_lamports = banks_client
+        .get_balance(lamport_fee_vault_address)
+        .await
+        .unwrap();
+    assert_eq!(
+        lamport_fee_vault_lamports,
+        Rent::default().minimum_balance(LamportFeeVault::LEN) + MAKE_LAMPORT_FEE
+    );
+}
+
+#[tokio::test]
+async fn it_makes_offer_with_transfer_fee() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints (token A on Token-2022 with transfer fee, token B on SPL Token)
+    let token_a_mint_address = add_mint_account_with_transfer_fee(
+        &mut program_test,
+        TOKEN_2022_DECIMALS,
+        TRANSFER_FEE_BPS,
+    );
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(
+            &escrow_program::ID,
+            Pubkey::new_unique(),
+            MAKER_FEE_BPS,
+            TAKER_FEE_BPS,
+        )
+        .0,
+    );
+
+    // Create maker keypair, and token A token account (ATA)
+    let maker_keypair = Keypair::new();
+    let maker_token_a_account_pubkey = get_associated_token_address_with_program_id(
+        &maker_keypair.pubkey(),
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_fee_amount(
+        &mut program_test,
+        maker_token_a_account_pubkey,
+        token_a_mint_address,
+        maker_keypair.pubkey(),
+        MAKER_TOKEN_A_BALANCE,
+        0,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call make offer instruction
+    let make_offer_instruction = make_offer_ix(
+        &maker_keypair.pubkey(),
+        &maker_token_a_account_pubkey,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token_2022::ID,
+        &payer_keypair.pubkey(),
+        MakeOfferArgs {
+            id: OFFER_ID,
+            token_a_offered_amount: TOKEN_A_OFFERED,
+            token_b_wanted_amount: TOKEN_B_WANTED,
+            expires_at: None,
+            allowed_taker: None,
+            taker_allowlist_root: None,
+            has_fee_exemption: false,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[make_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the offer records the amount escrowed after the transfer fee
+    let (offer_address, _bump) =
+        Offer::find_program_address(&escrow_program::ID, &maker_keypair.pubkey(), OFFER_ID);
+    let offer_after_make = get_program_account::<Offer>(&banks_client, offer_address).await;
+    assert_eq!(
+        offer_after_make.token_a_offered_amount,
+        TOKEN_A_OFFERED - TOKEN_A_TRANSFER_FEE
+    );
+
+    let vault_address = get_associated_token_address_with_program_id(
+        &offer_address,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, vault_address).await,
+        TOKEN_A_OFFERED - TOKEN_A_TRANSFER_FEE
+    );
+}
+
+#[tokio::test]
+async fn it_makes_offer_with_transfer_hook() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+    add_transfer_hook_program(&mut program_test);
+
+    // Load token mints (token A on Token-2022 with transfer hook, token B on SPL Token)
+    let token_a_mint_address =
+        add_mint_account_with_transfer_hook(&mut program_test, TOKEN_2022_DECIMALS);
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(
+            &escrow_program::ID,
+            Pubkey::new_unique(),
+            MAKER_FEE_BPS,
+            TAKER_FEE_BPS,
+        )
+        .0,
+    );
+
+    // Create maker keypair, and token A token account (ATA)
+    let maker_keypair = Keypair::new();
+    let maker_token_a_account_pubkey = get_associated_token_address_with_program_id(
+        &maker_keypair.pubkey(),
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_hook_account(
+        &mut program_test,
+        maker_token_a_account_pubkey,
+        token_a_mint_address,
+        maker_keypair.pubkey(),
+        MAKER_TOKEN_A_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    let make_offer_args = || MakeOfferArgs {
+        id: OFFER_ID,
+        token_a_offered_amount: TOKEN_A_OFFERED,
+        token_b_wanted_amount: TOKEN_B_WANTED,
+        expires_at: None,
+        allowed_taker: None,
+        taker_allowlist_root: None,
+        has_fee_exemption: false,
+    };
+
+    // Call make offer instruction without the transfer hook accounts
+    let make_offer_instruction = make_offer_ix(
+        &maker_keypair.pubkey(),
+        &maker_token_a_account_pubkey,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token_2022::ID,
+        &payer_keypair.pubkey(),
+        make_offer_args(),
+    );
+    let result = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[make_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await;
+    assert!(result.is_err());
+
+    // Call make offer instruction with the transfer hook accounts resolved
+    let make_offer_instruction = make_offer_ix_with_extra_metas(
+        &maker_keypair.pubkey(),
+        &maker_token_a_account_pubkey,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token_2022::ID,
+        &payer_keypair.pubkey(),
+        make_offer_args(),
+        |address| {
+            let banks_client = banks_client.clone();
+            async move {
+                let account = banks_client.get_account(address).await?;
+                Ok::<_, AccountFetchError>(account.map(|account| account.data))
+            }
+        },
+    )
+    .await
+    .unwrap();
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[make_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let (offer_address, _bump) =
+        Offer::find_program_address(&escrow_program::ID, &maker_keypair.pubkey(), OFFER_ID);
+    let vault_address = get_associated_token_address_with_program_id(
+        &offer_address,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, vault_address).await,
+        TOKEN_A_OFFERED
+    );
+}
+
+#[tokio::test]
+async fn it_makes_offer_with_native_sol() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token B mint (token A is native SOL, wrapped from the maker's wallet)
+    let token_a_mint_address = spl_token::native_mint::ID;
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    add_escrow_state_account(
+        &mut program_test,
+        EscrowState::new(
+            &escrow_program::ID,
+            Pubkey::new_unique(),
+            MAKER_FEE_BPS,
+            TAKER_FEE_BPS,
+        )
+        .0,
+    );
+
+    // Create maker keypair with SOL, without any token A token account
+    let maker_keypair = Keypair::new();
+    let maker_token_a_account_pubkey =
+        get_associated_token_address(&maker_keypair.pubkey(), &token_a_mint_address);
+    add_wallet_account(&mut program_test, maker_keypair.pubkey(), MAKER_LAMPORTS);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call make offer instruction
+    let make_offer_instruction = make_offer_ix(
+        &maker_keypair.pubkey(),
+        &maker_token_a_account_pubkey,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &payer_keypair.pubkey(),
+        MakeOfferArgs {
+            id: OFFER_ID,
+            token_a_offered_amount: TOKEN_A_OFFERED,
+            token_b_wanted_amount: TOKEN_B_WANTED,
+            expires_at: None,
+            allowed_taker: None,
+            taker_allowlist_root: None,
+            has_fee_exemption: false,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[make_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let (offer_address, _bump) =
+        Offer::find_program_address(&escrow_program::ID, &maker_keypair.pubkey(), OFFER_ID);
+    let offer_after_make = get_program_account::<Offer>(&banks_client, offer_address).await;
+    assert_eq!(offer_after_make.token_a_mint, token_a_mint_address);
+    assert_eq!(offer_after_make.token_a_offered_amount, TOKEN_A_OFFERED);
+
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    let vault_balance_after_make = get_token_balance(&banks_client, vault_address).await;
+    assert_eq!(vault_balance_after_make, TOKEN_A_OFFERED);
+
+    let maker_lamports_after_make = banks_client
+        .get_balance(maker_keypair.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(maker_lamports_after_make, MAKER_LAMPORTS - TOKEN_A_OFFERED);
+}
+
+#[tokio::test]
+async fn it_fails_to_make_offer_when_paused() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account, with making offers paused
+    let (mut escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    escrow_state.is_make_paused = true;
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Create maker keypair, and token A token account (ATA)
+    let maker_keypair = Keypair::new();
+    let maker_token_a_account_pubkey =
+        get_associated_token_address(&maker_keypair.pubkey(), &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        maker_token_a_account_pubkey,
+        token_a_mint_address,
+        maker_keypair.pubkey(),
+        MAKER_TOKEN_A_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call make offer instruction
+    let make_offer_instruction = make_offer_ix(
+        &maker_keypair.pubkey(),
+        &maker_token_a_account_pubkey,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &payer_keypair.pubkey(),
+        MakeOfferArgs {
+            id: OFFER_ID,
+            token_a_offered_amount: TOKEN_A_OFFERED,
+            token_b_wanted_amount: TOKEN_B_WANTED,
+            expires_at: None,
+            allowed_taker: None,
+            taker_allowlist_root: None,
+            has_fee_exemption: false,
+        },
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[make_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::MakePaused as u32)
+        )
+    );
+}
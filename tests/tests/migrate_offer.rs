@@ -0,0 +1,159 @@
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::get_associated_token_address;
+
+use escrow_program::{
+    errors::EscrowError,
+    instructions::migrate_offer_ix,
+    state::{AccountType, LegacyOffer, Offer, ProgramAccount},
+};
+
+use crate::utils::{
+    add_legacy_offer_account, add_test_fixture_from_file, add_token_account, get_program_account,
+    prepare_program_test,
+};
+
+const OFFER_ID: u64 = 0;
+const TOKEN_A_OFFERED: u64 = 69;
+const TOKEN_B_WANTED: u64 = 420;
+
+#[tokio::test]
+async fn it_migrates_offer() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize an offer (and its vault account) in the legacy layout
+    let maker_pubkey = Pubkey::new_unique();
+    let (offer_address, bump) =
+        Offer::find_program_address(&escrow_program::ID, &maker_pubkey, OFFER_ID);
+    add_legacy_offer_account(
+        &mut program_test,
+        LegacyOffer {
+            id: OFFER_ID,
+            maker: maker_pubkey,
+            token_a_mint: token_a_mint_address,
+            token_b_mint: token_b_mint_address,
+            token_b_wanted_amount: TOKEN_B_WANTED,
+            bump,
+        },
+    );
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call migrate offer instruction (signed by a third party)
+    let migrate_offer_instruction = migrate_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &spl_token::ID,
+        &payer_keypair.pubkey(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[migrate_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let offer_account = banks_client
+        .get_account(offer_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(offer_account.data.len(), Offer::LEN);
+
+    let offer_after_migrate = get_program_account::<Offer>(&banks_client, offer_address).await;
+    assert_eq!(offer_after_migrate.account_type, AccountType::Offer);
+    assert_eq!(offer_after_migrate.version, Offer::VERSION);
+    assert_eq!(offer_after_migrate.id, OFFER_ID);
+    assert_eq!(offer_after_migrate.maker, maker_pubkey);
+    assert_eq!(offer_after_migrate.token_a_mint, token_a_mint_address);
+    assert_eq!(offer_after_migrate.token_b_mint, token_b_mint_address);
+    assert_eq!(offer_after_migrate.token_a_offered_amount, TOKEN_A_OFFERED);
+    assert_eq!(offer_after_migrate.token_b_wanted_amount, TOKEN_B_WANTED);
+    assert_eq!(offer_after_migrate.rent_payer, maker_pubkey);
+}
+
+#[tokio::test]
+async fn it_fails_to_migrate_offer_twice() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize an offer (and its vault account) in the legacy layout
+    let maker_pubkey = Pubkey::new_unique();
+    let (offer_address, bump) =
+        Offer::find_program_address(&escrow_program::ID, &maker_pubkey, OFFER_ID);
+    add_legacy_offer_account(
+        &mut program_test,
+        LegacyOffer {
+            id: OFFER_ID,
+            maker: maker_pubkey,
+            token_a_mint: token_a_mint_address,
+            token_b_mint: token_b_mint_address,
+            token_b_wanted_amount: TOKEN_B_WANTED,
+            bump,
+        },
+    );
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call migrate offer instruction twice
+    let migrate_offer_instruction = migrate_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &spl_token::ID,
+        &payer_keypair.pubkey(),
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[migrate_offer_instruction.clone(), migrate_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(EscrowError::AccountAlreadyMigrated as u32)
+        )
+    );
+}
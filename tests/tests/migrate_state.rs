@@ -0,0 +1,127 @@
+use solana_sdk::{
+    instruction::InstructionError,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+use escrow_program::{
+    errors::EscrowError,
+    instructions::{migrate_state_ix, set_fees::SetFeesArgs, set_fees_ix},
+    state::{AccountType, EscrowState, FeeRounding, LegacyEscrowState, ProgramAccount},
+};
+
+use crate::utils::{add_legacy_escrow_state_account, get_program_account, prepare_program_test};
+
+const MAKER_FEE_BPS: u16 = 100;
+const TAKER_FEE_BPS: u16 = 500;
+const NEW_MAKER_FEE_BPS: u16 = 1337;
+
+#[tokio::test]
+async fn it_migrates_state() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account in the legacy layout
+    let manager_keypair = Keypair::new();
+    let (escrow_state_address, bump) = EscrowState::find_program_address(&escrow_program::ID);
+    add_legacy_escrow_state_account(
+        &mut program_test,
+        LegacyEscrowState {
+            manager: manager_keypair.pubkey(),
+            maker_fee_bps: MAKER_FEE_BPS,
+            taker_fee_bps: TAKER_FEE_BPS,
+            bump,
+        },
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call migrate state instruction, then set fees to use the migrated state
+    let migrate_state_instruction =
+        migrate_state_ix(&manager_keypair.pubkey(), &payer_keypair.pubkey());
+    let set_fees_instruction = set_fees_ix(
+        &manager_keypair.pubkey(),
+        SetFeesArgs {
+            maker_fee_bps: NEW_MAKER_FEE_BPS,
+            taker_fee_bps: TAKER_FEE_BPS,
+            effective_at: None,
+            fee_rounding: FeeRounding::default(),
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[migrate_state_instruction, set_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state_account = banks_client
+        .get_account(escrow_state_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(escrow_state_account.data.len(), EscrowState::LEN);
+
+    let escrow_state_after_migrate =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+    assert_eq!(
+        escrow_state_after_migrate.account_type,
+        AccountType::EscrowState
+    );
+    assert_eq!(escrow_state_after_migrate.version, EscrowState::VERSION);
+    assert_eq!(escrow_state_after_migrate.manager, manager_keypair.pubkey());
+    assert_eq!(escrow_state_after_migrate.maker_fee_bps, NEW_MAKER_FEE_BPS);
+    assert_eq!(escrow_state_after_migrate.taker_fee_bps, TAKER_FEE_BPS);
+    assert_eq!(escrow_state_after_migrate.bump, bump);
+}
+
+#[tokio::test]
+async fn it_fails_to_migrate_state_twice() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account in the legacy layout
+    let manager_keypair = Keypair::new();
+    let (_escrow_state_address, bump) = EscrowState::find_program_address(&escrow_program::ID);
+    add_legacy_escrow_state_account(
+        &mut program_test,
+        LegacyEscrowState {
+            manager: manager_keypair.pubkey(),
+            maker_fee_bps: MAKER_FEE_BPS,
+            taker_fee_bps: TAKER_FEE_BPS,
+            bump,
+        },
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call migrate state instruction twice
+    let migrate_state_instruction =
+        migrate_state_ix(&manager_keypair.pubkey(), &payer_keypair.pubkey());
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[migrate_state_instruction.clone(), migrate_state_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(EscrowError::AccountAlreadyMigrated as u32)
+        )
+    );
+}
@@ -1,7 +1,27 @@
+mod accept_manager;
+mod add_fee_exemption;
+mod cancel_manager_nomination;
 mod cancel_offer;
+mod cancel_pending_fees;
+mod close_fee_override;
 mod collect_fee;
+mod collect_fees;
+mod collect_lamport_fees;
+mod create_fee_override;
+mod expire_offer;
 mod initialize;
 mod make_offer;
+mod migrate_offer;
+mod migrate_state;
+mod remove_fee_exemption;
+mod set_fee_change_delay;
+mod set_fee_collector;
+mod set_fee_policy;
 mod set_fees;
+mod set_lamport_fees;
 mod set_manager;
+mod set_pause;
+mod set_referral_share;
 mod take_offer;
+mod update_fee_override;
+mod update_offer;
@@ -0,0 +1,59 @@
+use solana_sdk::{
+    pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer, transaction::Transaction,
+};
+
+use escrow_program::{
+    instructions::remove_fee_exemption_ix,
+    state::{EscrowState, FeeExemption, ProgramAccount},
+};
+
+use crate::utils::{add_escrow_state_account, add_program_account, prepare_program_test};
+
+#[tokio::test]
+async fn it_removes_fee_exemption() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, _) = EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize a fee exemption
+    let wallet_address = Pubkey::new_unique();
+    let (fee_exemption, fee_exemption_address) =
+        FeeExemption::new(&escrow_program::ID, wallet_address, 10_000, None);
+    add_program_account(&mut program_test, fee_exemption_address, fee_exemption);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call remove fee exemption instruction
+    let remove_fee_exemption_instruction =
+        remove_fee_exemption_ix(&manager_keypair.pubkey(), &wallet_address);
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[remove_fee_exemption_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let fee_exemption_account = banks_client
+        .get_account(fee_exemption_address)
+        .await
+        .unwrap();
+    assert!(fee_exemption_account.is_none());
+
+    let manager_lamports = banks_client
+        .get_balance(manager_keypair.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        manager_lamports,
+        Rent::default().minimum_balance(FeeExemption::LEN)
+    );
+}
@@ -0,0 +1,168 @@
+use solana_sdk::{
+    clock::Clock,
+    instruction::InstructionError,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+use escrow_program::{
+    errors::EscrowError,
+    instructions::{
+        set_fee_change_delay::SetFeeChangeDelayArgs, set_fee_change_delay_ix,
+        set_fees::SetFeesArgs, set_fees_ix,
+    },
+    state::{EscrowState, FeeRates, FeeRounding, PendingFeeChangeDelay, PendingFees},
+};
+
+use crate::utils::{add_escrow_state_account, get_program_account, prepare_program_test};
+
+const FEE_CHANGE_DELAY: u32 = 86_400;
+const MAKER_FEE_BPS: u16 = 1337;
+const TAKER_FEE_BPS: u16 = 420;
+
+#[tokio::test]
+async fn it_sets_fee_change_delay() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call set fee change delay instruction
+    let set_fee_change_delay_instruction = set_fee_change_delay_ix(
+        &manager_keypair.pubkey(),
+        SetFeeChangeDelayArgs {
+            fee_change_delay: FEE_CHANGE_DELAY,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_fee_change_delay_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state_after_set =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+
+    assert_eq!(escrow_state_after_set.fee_change_delay, FEE_CHANGE_DELAY);
+}
+
+#[tokio::test]
+async fn it_decreases_fee_change_delay_after_current_delay() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account, with a fee change delay
+    let manager_keypair = Keypair::new();
+    let (mut escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    escrow_state.fee_change_delay = FEE_CHANGE_DELAY;
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+    let clock = banks_client.get_sysvar::<Clock>().await.unwrap();
+
+    // Call set fee change delay instruction, removing the delay
+    let set_fee_change_delay_instruction = set_fee_change_delay_ix(
+        &manager_keypair.pubkey(),
+        SetFeeChangeDelayArgs {
+            fee_change_delay: 0,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_fee_change_delay_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the current delay is kept until it has passed
+    let escrow_state_after_set =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+
+    assert_eq!(escrow_state_after_set.fee_change_delay, FEE_CHANGE_DELAY);
+    assert_eq!(
+        escrow_state_after_set.pending_fee_change_delay,
+        Some(PendingFeeChangeDelay {
+            fee_change_delay: 0,
+            effective_at: clock.unix_timestamp + i64::from(FEE_CHANGE_DELAY),
+        })
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_bring_pending_fees_forward_by_decreasing_fee_change_delay() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account, with a fee change delay and fees already announced
+    let manager_keypair = Keypair::new();
+    let (mut escrow_state, _escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    escrow_state.fee_change_delay = FEE_CHANGE_DELAY;
+    escrow_state.pending_fees = Some(PendingFees {
+        fee_rates: FeeRates {
+            maker_fee_bps: MAKER_FEE_BPS,
+            taker_fee_bps: TAKER_FEE_BPS,
+        },
+        effective_at: i64::MAX,
+        fee_rounding: FeeRounding::default(),
+    });
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+    let clock = banks_client.get_sysvar::<Clock>().await.unwrap();
+
+    // Call set fee change delay instruction removing the delay, then set fees in effect right away
+    let set_fee_change_delay_instruction = set_fee_change_delay_ix(
+        &manager_keypair.pubkey(),
+        SetFeeChangeDelayArgs {
+            fee_change_delay: 0,
+        },
+    );
+    let set_fees_instruction = set_fees_ix(
+        &manager_keypair.pubkey(),
+        SetFeesArgs {
+            maker_fee_bps: MAKER_FEE_BPS,
+            taker_fee_bps: TAKER_FEE_BPS,
+            effective_at: Some(clock.unix_timestamp),
+            fee_rounding: FeeRounding::default(),
+        },
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_fee_change_delay_instruction, set_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(EscrowError::FeeChangeTooEarly as u32)
+        )
+    );
+}
@@ -0,0 +1,58 @@
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+
+use escrow_program::{
+    instructions::{set_fee_collector::SetFeeCollectorArgs, set_fee_collector_ix},
+    state::EscrowState,
+};
+
+use crate::utils::{add_escrow_state_account, get_program_account, prepare_program_test};
+
+#[tokio::test]
+async fn it_sets_fee_collector() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Create fee collector and fee destination pubkeys
+    let fee_collector_pubkey = Pubkey::new_unique();
+    let fee_destination_pubkey = Pubkey::new_unique();
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call set fee collector instruction
+    let set_fee_collector_instruction = set_fee_collector_ix(
+        &manager_keypair.pubkey(),
+        SetFeeCollectorArgs {
+            fee_collector: Some(fee_collector_pubkey),
+            fee_destination: Some(fee_destination_pubkey),
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_fee_collector_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state_after_set =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+
+    assert_eq!(
+        escrow_state_after_set.fee_collector,
+        Some(fee_collector_pubkey)
+    );
+    assert_eq!(
+        escrow_state_after_set.fee_destination,
+        Some(fee_destination_pubkey)
+    );
+}
@@ -0,0 +1,46 @@
+use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction};
+
+use escrow_program::{
+    instructions::{set_fee_policy::SetFeePolicyArgs, set_fee_policy_ix},
+    state::{EscrowState, FeePolicy},
+};
+
+use crate::utils::{add_escrow_state_account, get_program_account, prepare_program_test};
+
+#[tokio::test]
+async fn it_sets_fee_policy() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call set fee policy instruction
+    let set_fee_policy_instruction = set_fee_policy_ix(
+        &manager_keypair.pubkey(),
+        SetFeePolicyArgs {
+            fee_policy: FeePolicy::AtCreation,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_fee_policy_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state_after_set =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+
+    assert_eq!(escrow_state_after_set.fee_policy, FeePolicy::AtCreation);
+}
@@ -1,16 +1,29 @@
-use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction};
+use solana_sdk::{
+    clock::Clock,
+    instruction::InstructionError,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
 
 use escrow_program::{
+    errors::EscrowError,
     instructions::{set_fees::SetFeesArgs, set_fees_ix},
-    state::EscrowState,
+    state::{EscrowState, FeeRates, FeeRounding, RoundingMode},
 };
 
-use crate::utils::{add_escrow_state_account, prepare_program_test};
+use crate::utils::{add_escrow_state_account, get_program_account, prepare_program_test};
 
 const ORIG_MAKER_FEE_BPS: u16 = 0;
 const ORIG_TAKER_FEE_BPS: u16 = 0;
 const MAKER_FEE_BPS: u16 = 1337;
 const TAKER_FEE_BPS: u16 = 420;
+const FEE_ROUNDING: FeeRounding = FeeRounding {
+    mode: RoundingMode::HalfUp,
+    min_maker_fee: Some(1),
+    min_taker_fee: None,
+};
+const FEE_CHANGE_DELAY: u32 = 86_400;
 
 #[tokio::test]
 async fn it_sets_fees() {
@@ -36,6 +49,8 @@ async fn it_sets_fees() {
         SetFeesArgs {
             maker_fee_bps: MAKER_FEE_BPS,
             taker_fee_bps: TAKER_FEE_BPS,
+            effective_at: None,
+            fee_rounding: FEE_ROUNDING,
         },
     );
     banks_client
@@ -49,15 +64,132 @@ async fn it_sets_fees() {
         .unwrap();
 
     // Check the result
-    let (escrow_state_maker_fee_after_set, escrow_state_taker_fee_after_set) = {
-        let escrow_state = banks_client
-            .get_account_data_with_borsh::<EscrowState>(escrow_state_address)
-            .await
-            .unwrap();
+    let (
+        escrow_state_maker_fee_after_set,
+        escrow_state_taker_fee_after_set,
+        escrow_state_fee_rounding_after_set,
+    ) = {
+        let escrow_state =
+            get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
 
-        (escrow_state.maker_fee_bps, escrow_state.taker_fee_bps)
+        (
+            escrow_state.maker_fee_bps,
+            escrow_state.taker_fee_bps,
+            escrow_state.fee_rounding,
+        )
     };
 
     assert_eq!(escrow_state_maker_fee_after_set, MAKER_FEE_BPS);
     assert_eq!(escrow_state_taker_fee_after_set, TAKER_FEE_BPS);
+    assert_eq!(escrow_state_fee_rounding_after_set, FEE_ROUNDING);
+}
+
+#[tokio::test]
+async fn it_announces_fees_after_fee_change_delay() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account, with a fee change delay
+    let manager_keypair = Keypair::new();
+    let (mut escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        manager_keypair.pubkey(),
+        ORIG_MAKER_FEE_BPS,
+        ORIG_TAKER_FEE_BPS,
+    );
+    escrow_state.fee_change_delay = FEE_CHANGE_DELAY;
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+    let clock = banks_client.get_sysvar::<Clock>().await.unwrap();
+
+    // Call set fees instruction
+    let set_fees_instruction = set_fees_ix(
+        &manager_keypair.pubkey(),
+        SetFeesArgs {
+            maker_fee_bps: MAKER_FEE_BPS,
+            taker_fee_bps: TAKER_FEE_BPS,
+            effective_at: None,
+            fee_rounding: FEE_ROUNDING,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the fees are pending until the fee change delay has passed
+    let escrow_state_after_set =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+    let pending_fees = escrow_state_after_set.pending_fees.unwrap();
+
+    assert_eq!(escrow_state_after_set.maker_fee_bps, ORIG_MAKER_FEE_BPS);
+    assert_eq!(escrow_state_after_set.taker_fee_bps, ORIG_TAKER_FEE_BPS);
+    assert_eq!(
+        pending_fees.fee_rates,
+        FeeRates {
+            maker_fee_bps: MAKER_FEE_BPS,
+            taker_fee_bps: TAKER_FEE_BPS,
+        }
+    );
+    assert!(pending_fees.effective_at >= clock.unix_timestamp + i64::from(FEE_CHANGE_DELAY));
+    assert_eq!(pending_fees.fee_rounding, FEE_ROUNDING);
+    assert_eq!(escrow_state_after_set.fee_rounding, FeeRounding::default());
+}
+
+#[tokio::test]
+async fn it_fails_to_set_fees_before_fee_change_delay() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account, with a fee change delay
+    let manager_keypair = Keypair::new();
+    let (mut escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        manager_keypair.pubkey(),
+        ORIG_MAKER_FEE_BPS,
+        ORIG_TAKER_FEE_BPS,
+    );
+    escrow_state.fee_change_delay = FEE_CHANGE_DELAY;
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+    let clock = banks_client.get_sysvar::<Clock>().await.unwrap();
+
+    // Call set fees instruction, in effect right away
+    let set_fees_instruction = set_fees_ix(
+        &manager_keypair.pubkey(),
+        SetFeesArgs {
+            maker_fee_bps: MAKER_FEE_BPS,
+            taker_fee_bps: TAKER_FEE_BPS,
+            effective_at: Some(clock.unix_timestamp),
+            fee_rounding: FEE_ROUNDING,
+        },
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::FeeChangeTooEarly as u32)
+        )
+    );
 }
@@ -0,0 +1,176 @@
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+use escrow_program::{
+    errors::EscrowError,
+    instructions::{set_lamport_fees::SetLamportFeesArgs, set_lamport_fees_ix},
+    state::{EscrowState, LamportFeeVault, ProgramAccount},
+};
+
+use crate::utils::{
+    add_escrow_state_account, add_wallet_account, get_program_account, prepare_program_test,
+};
+
+const MAKE_LAMPORT_FEE: u64 = 5_000;
+const TAKE_LAMPORT_FEE: u64 = 10_000;
+const PREFUNDED_LAMPORTS: u64 = 1; // NB: should be lower than the rent exemption
+
+#[tokio::test]
+async fn it_sets_lamport_fees() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call set lamport fees instruction twice, creating the lamport fee vault once
+    let set_lamport_fees_instruction = set_lamport_fees_ix(
+        &manager_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        SetLamportFeesArgs {
+            make_lamport_fee: 0,
+            take_lamport_fee: 0,
+        },
+    );
+    let reset_lamport_fees_instruction = set_lamport_fees_ix(
+        &manager_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        SetLamportFeesArgs {
+            make_lamport_fee: MAKE_LAMPORT_FEE,
+            take_lamport_fee: TAKE_LAMPORT_FEE,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_lamport_fees_instruction, reset_lamport_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state_after_set =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+    assert_eq!(escrow_state_after_set.make_lamport_fee, MAKE_LAMPORT_FEE);
+    assert_eq!(escrow_state_after_set.take_lamport_fee, TAKE_LAMPORT_FEE);
+
+    let (lamport_fee_vault_address, lamport_fee_vault_bump) =
+        LamportFeeVault::find_program_address(&escrow_program::ID);
+    let lamport_fee_vault =
+        get_program_account::<LamportFeeVault>(&banks_client, lamport_fee_vault_address).await;
+    assert_eq!(lamport_fee_vault.bump, lamport_fee_vault_bump);
+}
+
+#[tokio::test]
+async fn it_sets_lamport_fees_with_prefunded_lamport_fee_vault() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, _escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Fund the lamport fee vault address before its creation
+    let (lamport_fee_vault_address, lamport_fee_vault_bump) =
+        LamportFeeVault::find_program_address(&escrow_program::ID);
+    add_wallet_account(
+        &mut program_test,
+        lamport_fee_vault_address,
+        PREFUNDED_LAMPORTS,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call set lamport fees instruction, creating the lamport fee vault
+    let set_lamport_fees_instruction = set_lamport_fees_ix(
+        &manager_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        SetLamportFeesArgs {
+            make_lamport_fee: MAKE_LAMPORT_FEE,
+            take_lamport_fee: TAKE_LAMPORT_FEE,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_lamport_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the funded lamports are topped up to the rent exemption
+    let lamport_fee_vault_account = banks_client
+        .get_account(lamport_fee_vault_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(lamport_fee_vault_account.owner, escrow_program::ID);
+    assert_eq!(
+        lamport_fee_vault_account.lamports,
+        Rent::default().minimum_balance(LamportFeeVault::LEN)
+    );
+
+    let lamport_fee_vault =
+        get_program_account::<LamportFeeVault>(&banks_client, lamport_fee_vault_address).await;
+    assert_eq!(lamport_fee_vault.bump, lamport_fee_vault_bump);
+}
+
+#[tokio::test]
+async fn it_fails_to_set_lamport_fees_by_non_manager() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let (escrow_state, _) = EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call set lamport fees instruction, signed by a non-manager
+    let non_manager_keypair = Keypair::new();
+    let set_lamport_fees_instruction = set_lamport_fees_ix(
+        &non_manager_keypair.pubkey(),
+        &payer_keypair.pubkey(),
+        SetLamportFeesArgs {
+            make_lamport_fee: MAKE_LAMPORT_FEE,
+            take_lamport_fee: TAKE_LAMPORT_FEE,
+        },
+    );
+    let result = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_lamport_fees_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &non_manager_keypair],
+            last_blockhash,
+        ))
+        .await;
+
+    // Check the result
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::ManagerKeyUnauthorized as u32)
+        )
+    );
+}
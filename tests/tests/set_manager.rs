@@ -2,7 +2,7 @@ use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction
 
 use escrow_program::{instructions::set_manager_ix, state::EscrowState};
 
-use crate::utils::{add_escrow_state_account, prepare_program_test};
+use crate::utils::{add_escrow_state_account, get_program_account, prepare_program_test};
 
 #[tokio::test]
 async fn it_sets_manager() {
@@ -21,7 +21,7 @@ async fn it_sets_manager() {
     // [Start Test]
     let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
 
-    // Call set fees instruction
+    // Call set manager instruction
     let set_manager_instruction = set_manager_ix(&manager_keypair.pubkey(), &new_manager_pubkey);
     banks_client
         .process_transaction(Transaction::new_signed_with_payer(
@@ -34,10 +34,12 @@ async fn it_sets_manager() {
         .unwrap();
 
     // Check the result
-    let escrow_state_after_set = banks_client
-        .get_account_data_with_borsh::<EscrowState>(escrow_state_address)
-        .await
-        .unwrap();
-
-    assert_eq!(escrow_state_after_set.manager, new_manager_pubkey,);
+    let escrow_state_after_set =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+
+    assert_eq!(escrow_state_after_set.manager, manager_keypair.pubkey());
+    assert_eq!(
+        escrow_state_after_set.pending_manager,
+        Some(new_manager_pubkey)
+    );
 }
@@ -0,0 +1,51 @@
+use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction};
+
+use escrow_program::{
+    instructions::{set_referral_share::SetReferralShareArgs, set_referral_share_ix},
+    state::EscrowState,
+};
+
+use crate::utils::{add_escrow_state_account, get_program_account, prepare_program_test};
+
+const REFERRAL_SHARE_BPS: u16 = 2_000;
+
+#[tokio::test]
+async fn it_sets_referral_share() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call set referral share instruction
+    let set_referral_share_instruction = set_referral_share_ix(
+        &manager_keypair.pubkey(),
+        SetReferralShareArgs {
+            referral_share_bps: REFERRAL_SHARE_BPS,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[set_referral_share_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state_after_set =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+
+    assert_eq!(
+        escrow_state_after_set.referral_share_bps,
+        REFERRAL_SHARE_BPS
+    );
+}
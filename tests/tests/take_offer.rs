@@ -1,14 +1,43 @@
-use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
-use spl_associated_token_account::get_associated_token_address;
+use solana_sdk::{
+    instruction::InstructionError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
 use spl_token::state::Account as TokenAccount;
+use spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    offchain::AccountFetchError,
+    state::Mint,
+};
 
 use escrow_program::{
-    instructions::take_offer_ix,
-    state::{EscrowState, Offer},
+    consts::MAX_BPS_VALUE,
+    errors::EscrowError,
+    instructions::{
+        partial_take_offer_ix,
+        take_offer::{PartialTakeOfferArgs, TakeOfferArgs},
+        take_offer_ix, take_offer_ix_with_extra_metas,
+    },
+    merkle,
+    state::{
+        EscrowState, FeeExemption, FeeOverride, FeePolicy, FeeRates, FeeRounding, LamportFeeVault,
+        Offer, PendingFees, ProgramAccount, RoundingMode,
+    },
 };
 
 use crate::utils::{
-    add_escrow_state_account, add_offer_account, add_test_fixture_from_file, add_token_account,
+    add_escrow_state_account, add_mint_account, add_mint_account_with_transfer_fee,
+    add_mint_account_with_transfer_hook, add_native_token_account, add_program_account,
+    add_test_fixture_from_file, add_token_account, add_token_account_with_program_id,
+    add_token_account_with_transfer_fee_amount, add_token_account_with_transfer_hook_account,
+    add_transfer_hook_program, add_wallet_account, get_program_account, get_token_balance,
     prepare_program_test,
 };
 
@@ -18,6 +47,20 @@ const TOKEN_A_OFFERED: u64 = 69;
 const TOKEN_B_WANTED: u64 = 420;
 const MAKER_FEE_BPS: u16 = 100;
 const TAKER_FEE_BPS: u16 = 500;
+const OVERRIDE_MAKER_FEE_BPS: u16 = 10;
+const OVERRIDE_TAKER_FEE_BPS: u16 = 0;
+const REFERRAL_SHARE_BPS: u16 = 5_000;
+const MAKER_FEE_DISCOUNT_BPS: u16 = 10_000;
+const TAKER_FEE_DISCOUNT_BPS: u16 = 5_000;
+const TAKE_LAMPORT_FEE: u64 = 10_000;
+const MIN_FEE: u64 = 1; // NB: should be above the fees at 1 bps
+const TOKEN_A_PARTIALLY_TAKEN: u64 = 23; // NB: should be lower than TOKEN_A_OFFERED
+const EXPIRES_AT: i64 = 1; // NB: should be in the past
+const TOKEN_2022_DECIMALS: u8 = 9;
+const TAKER_WSOL_BALANCE: u64 = 1_000;
+const TRANSFER_FEE_BPS: u16 = 100;
+const VAULT_WITHHELD_AMOUNT: u64 = 7;
+const WALLET_LAMPORTS: u64 = 1_000_000_000;
 
 #[tokio::test]
 async fn it_takes_offer() {
@@ -37,8 +80,9 @@ async fn it_takes_offer() {
     );
     add_escrow_state_account(&mut program_test, escrow_state);
 
-    // Create maker pubkey //, and token A token account address (ATA)
+    // Create maker pubkey, and pubkey of the original funder of the offer
     let maker_pubkey = Pubkey::new_unique();
+    let rent_payer_pubkey = Pubkey::new_unique();
 
     // Initialize an offer (and its vault account) to be taken
     let (offer, offer_address) = Offer::new(
@@ -47,9 +91,12 @@ async fn it_takes_offer() {
         maker_pubkey,
         token_a_mint_address,
         token_b_mint_address,
+        TOKEN_A_OFFERED,
         TOKEN_B_WANTED,
+        rent_payer_pubkey,
     );
-    add_offer_account(&mut program_test, offer);
+    let offer_rent = Rent::default().minimum_balance(Offer::LEN);
+    add_program_account(&mut program_test, offer_address, offer);
     let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
     add_token_account(
         &mut program_test,
@@ -79,10 +126,14 @@ async fn it_takes_offer() {
         &offer_address,
         &token_a_mint_address,
         &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
         &taker_token_b_account_pubkey,
         &maker_pubkey,
         &taker_keypair.pubkey(),
+        &rent_payer_pubkey,
         &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
     );
     banks_client
         .process_transaction(Transaction::new_signed_with_payer(
@@ -95,10 +146,8 @@ async fn it_takes_offer() {
         .unwrap();
 
     // Check the result
-    let escrow_state = banks_client
-        .get_account_data_with_borsh::<EscrowState>(escrow_state_address)
-        .await
-        .unwrap();
+    let escrow_state =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
     let token_a_fee_amount = escrow_state.get_token_a_fee(TOKEN_A_OFFERED).unwrap();
     let token_b_fee_amount = escrow_state.get_token_b_fee(TOKEN_B_WANTED).unwrap();
 
@@ -153,5 +202,3027 @@ async fn it_takes_offer() {
         TOKEN_B_WANTED - token_b_fee_amount
     );
 
-    // let escrow
+    let vault_rent = Rent::default().minimum_balance(TokenAccount::LEN);
+    let rent_payer_balance_after_take = banks_client.get_balance(rent_payer_pubkey).await.unwrap();
+    assert_eq!(rent_payer_balance_after_take, offer_rent + vault_rent);
+}
+
+#[tokio::test]
+async fn it_takes_offer_partially() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call partial take offer instruction
+    let partial_take_offer_instruction = partial_take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        PartialTakeOfferArgs {
+            token_a_amount: TOKEN_A_PARTIALLY_TAKEN,
+            taker_allowlist_proof: vec![],
+            max_token_b_to_pay: None,
+            min_token_a_to_receive: None,
+            has_fee_exemption: false,
+            referrer: None,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[partial_take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the offer is still alive with the remaining amounts
+    let token_b_paid = TOKEN_B_WANTED * TOKEN_A_PARTIALLY_TAKEN / TOKEN_A_OFFERED;
+    let offer_after_partial_take = get_program_account::<Offer>(&banks_client, offer_address).await;
+    assert_eq!(
+        offer_after_partial_take.token_a_offered_amount,
+        TOKEN_A_OFFERED - TOKEN_A_PARTIALLY_TAKEN
+    );
+    assert_eq!(
+        offer_after_partial_take.token_b_wanted_amount,
+        TOKEN_B_WANTED - token_b_paid
+    );
+
+    let vault_balance_after_partial_take = banks_client
+        .get_packed_account_data::<TokenAccount>(vault_address)
+        .await
+        .unwrap()
+        .amount;
+    assert_eq!(
+        vault_balance_after_partial_take,
+        TOKEN_A_OFFERED - TOKEN_A_PARTIALLY_TAKEN
+    );
+
+    // Take the rest of the offer
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the offer and its vault are closed on the final fill
+    assert!(banks_client
+        .get_account(offer_address)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(banks_client
+        .get_account(vault_address)
+        .await
+        .unwrap()
+        .is_none());
+
+    let taker_token_b_balance_after_take = banks_client
+        .get_packed_account_data::<TokenAccount>(taker_token_b_account_pubkey)
+        .await
+        .unwrap()
+        .amount;
+    assert_eq!(
+        taker_token_b_balance_after_take,
+        TAKER_TOKEN_B_BALANCE - TOKEN_B_WANTED
+    );
+}
+
+#[tokio::test]
+async fn it_takes_offer_with_fee_override() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize a fee override for the mint pair of the offer
+    let (fee_override, fee_override_address) = FeeOverride::new(
+        &escrow_program::ID,
+        token_a_mint_address,
+        Some(token_b_mint_address),
+        OVERRIDE_MAKER_FEE_BPS,
+        OVERRIDE_TAKER_FEE_BPS,
+    );
+    let fee_rates = fee_override.fee_rates();
+    add_program_account(&mut program_test, fee_override_address, fee_override);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction, with the fee overrides of the mints
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, fees are levied at the overridden rates
+    let token_a_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_a_mint_address),
+    )
+    .await;
+    let token_b_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_b_mint_address),
+    )
+    .await;
+    assert_eq!(
+        token_a_escrow_fee_balance_after_take,
+        fee_rates
+            .get_token_a_fee(TOKEN_A_OFFERED, &FeeRounding::default())
+            .unwrap()
+    );
+    assert_eq!(
+        token_b_escrow_fee_balance_after_take,
+        fee_rates
+            .get_token_b_fee(TOKEN_B_WANTED, &FeeRounding::default())
+            .unwrap()
+    );
+
+    let taker_token_a_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&taker_keypair.pubkey(), &token_a_mint_address),
+    )
+    .await;
+    assert_eq!(taker_token_a_balance_after_take, TOKEN_A_OFFERED);
+}
+
+#[tokio::test]
+async fn it_takes_offer_with_fee_override_for_token_b() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize a fee override for token B only
+    let (fee_override, fee_override_address) = FeeOverride::new(
+        &escrow_program::ID,
+        token_b_mint_address,
+        None,
+        OVERRIDE_MAKER_FEE_BPS,
+        OVERRIDE_TAKER_FEE_BPS,
+    );
+    let fee_rates = fee_override.fee_rates();
+    add_program_account(&mut program_test, fee_override_address, fee_override);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction, with the fee overrides of the mints
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, fees are levied at the overridden rates
+    let token_a_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_a_mint_address),
+    )
+    .await;
+    let token_b_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_b_mint_address),
+    )
+    .await;
+    assert_eq!(
+        token_a_escrow_fee_balance_after_take,
+        fee_rates
+            .get_token_a_fee(TOKEN_A_OFFERED, &FeeRounding::default())
+            .unwrap()
+    );
+    assert_eq!(
+        token_b_escrow_fee_balance_after_take,
+        fee_rates
+            .get_token_b_fee(TOKEN_B_WANTED, &FeeRounding::default())
+            .unwrap()
+    );
+
+    let taker_token_a_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&taker_keypair.pubkey(), &token_a_mint_address),
+    )
+    .await;
+    assert_eq!(taker_token_a_balance_after_take, TOKEN_A_OFFERED);
+}
+
+#[tokio::test]
+async fn it_takes_offer_with_referrer() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (mut escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    escrow_state.referral_share_bps = REFERRAL_SHARE_BPS;
+    let token_a_fee_amount = escrow_state.get_token_a_fee(TOKEN_A_OFFERED).unwrap();
+    let token_b_fee_amount = escrow_state.get_token_b_fee(TOKEN_B_WANTED).unwrap();
+    let token_a_referral_fee_amount = escrow_state.get_referral_fee(token_a_fee_amount).unwrap();
+    let token_b_referral_fee_amount = escrow_state.get_referral_fee(token_b_fee_amount).unwrap();
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Create maker and referrer pubkeys
+    let maker_pubkey = Pubkey::new_unique();
+    let referrer_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction with the referrer
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs {
+            referrer: Some(referrer_pubkey),
+            ..Default::default()
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, fees are shared with the referrer
+    let token_a_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_a_mint_address),
+    )
+    .await;
+    let token_b_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_b_mint_address),
+    )
+    .await;
+    assert_eq!(
+        token_a_escrow_fee_balance_after_take,
+        token_a_fee_amount - token_a_referral_fee_amount
+    );
+    assert_eq!(
+        token_b_escrow_fee_balance_after_take,
+        token_b_fee_amount - token_b_referral_fee_amount
+    );
+
+    let referrer_token_a_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&referrer_pubkey, &token_a_mint_address),
+    )
+    .await;
+    let referrer_token_b_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&referrer_pubkey, &token_b_mint_address),
+    )
+    .await;
+    assert_eq!(
+        referrer_token_a_balance_after_take,
+        token_a_referral_fee_amount
+    );
+    assert_eq!(
+        referrer_token_b_balance_after_take,
+        token_b_referral_fee_amount
+    );
+
+    let taker_token_a_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&taker_keypair.pubkey(), &token_a_mint_address),
+    )
+    .await;
+    assert_eq!(
+        taker_token_a_balance_after_take,
+        TOKEN_A_OFFERED - token_a_fee_amount
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_take_offer_with_taker_or_maker_as_referrer() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (mut escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    escrow_state.referral_share_bps = REFERRAL_SHARE_BPS;
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction with the taker, then the maker as referrer
+    for referrer_pubkey in [taker_keypair.pubkey(), maker_pubkey] {
+        let take_offer_instruction = take_offer_ix(
+            &offer_address,
+            &token_a_mint_address,
+            &token_b_mint_address,
+            &spl_token::ID,
+            &spl_token::ID,
+            &taker_token_b_account_pubkey,
+            &maker_pubkey,
+            &taker_keypair.pubkey(),
+            &maker_pubkey,
+            &payer_keypair.pubkey(),
+            TakeOfferArgs {
+                referrer: Some(referrer_pubkey),
+                ..Default::default()
+            },
+        );
+        let err = banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &[take_offer_instruction],
+                Some(&payer_keypair.pubkey()),
+                &[&payer_keypair, &taker_keypair],
+                last_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap();
+
+        // Check the result
+        assert_eq!(
+            err,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(EscrowError::ReferrerKeyUnauthorized as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn it_takes_offer_at_fee_rates_of_creation() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (mut escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    escrow_state.fee_policy = FeePolicy::AtCreation;
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken, made at other fee rates
+    let fee_rates = FeeRates {
+        maker_fee_bps: OVERRIDE_MAKER_FEE_BPS,
+        taker_fee_bps: OVERRIDE_TAKER_FEE_BPS,
+    };
+    let (mut offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    offer.fee_rates = Some(fee_rates);
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, fees are levied at the rates of creation
+    let token_a_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_a_mint_address),
+    )
+    .await;
+    let token_b_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_b_mint_address),
+    )
+    .await;
+    assert_eq!(
+        token_a_escrow_fee_balance_after_take,
+        fee_rates
+            .get_token_a_fee(TOKEN_A_OFFERED, &FeeRounding::default())
+            .unwrap()
+    );
+    assert_eq!(
+        token_b_escrow_fee_balance_after_take,
+        fee_rates
+            .get_token_b_fee(TOKEN_B_WANTED, &FeeRounding::default())
+            .unwrap()
+    );
+
+    let taker_token_a_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&taker_keypair.pubkey(), &token_a_mint_address),
+    )
+    .await;
+    assert_eq!(taker_token_a_balance_after_take, TOKEN_A_OFFERED);
+}
+
+#[tokio::test]
+async fn it_takes_offer_at_pending_fees_in_effect() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    let fee_rates = FeeRates {
+        maker_fee_bps: OVERRIDE_MAKER_FEE_BPS,
+        taker_fee_bps: OVERRIDE_TAKER_FEE_BPS,
+    };
+
+    // Initialize the escrow state account, with pending fees already in effect
+    let (mut escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    escrow_state.pending_fees = Some(PendingFees {
+        fee_rates,
+        effective_at: EXPIRES_AT,
+        fee_rounding: FeeRounding::default(),
+    });
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, fees are levied at the pending rates
+    let token_a_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_a_mint_address),
+    )
+    .await;
+    let token_b_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_b_mint_address),
+    )
+    .await;
+    assert_eq!(
+        token_a_escrow_fee_balance_after_take,
+        fee_rates
+            .get_token_a_fee(TOKEN_A_OFFERED, &FeeRounding::default())
+            .unwrap()
+    );
+    assert_eq!(
+        token_b_escrow_fee_balance_after_take,
+        fee_rates
+            .get_token_b_fee(TOKEN_B_WANTED, &FeeRounding::default())
+            .unwrap()
+    );
+
+    let taker_token_a_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&taker_keypair.pubkey(), &token_a_mint_address),
+    )
+    .await;
+    assert_eq!(taker_token_a_balance_after_take, TOKEN_A_OFFERED);
+}
+
+#[tokio::test]
+async fn it_takes_offer_with_minimum_fees_in_base_units() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints, with different decimals
+    let token_a_mint_address = add_mint_account(&mut program_test, 0, spl_token::ID);
+    let token_b_mint_address =
+        add_mint_account(&mut program_test, TOKEN_2022_DECIMALS, spl_token::ID);
+
+    // Initialize the escrow state account, with the same minimum fee on each side
+    let (mut escrow_state, escrow_state_address) =
+        EscrowState::new(&escrow_program::ID, Pubkey::new_unique(), 1, 1);
+    escrow_state.fee_rounding = FeeRounding {
+        mode: RoundingMode::Floor,
+        min_maker_fee: Some(MIN_FEE),
+        min_taker_fee: Some(MIN_FEE),
+    };
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the minimum fee is levied in base units whatever the decimals of the mint
+    let token_a_escrow_fee_address =
+        get_associated_token_address(&escrow_state_address, &token_a_mint_address);
+    let token_b_escrow_fee_address =
+        get_associated_token_address(&escrow_state_address, &token_b_mint_address);
+    assert_eq!(
+        get_token_balance(&banks_client, token_a_escrow_fee_address).await,
+        MIN_FEE
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, token_b_escrow_fee_address).await,
+        MIN_FEE
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_take_offer_without_fee_override_of_mints() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize a fee override for the mint pair of the offer
+    let (fee_override, fee_override_address) = FeeOverride::new(
+        &escrow_program::ID,
+        token_a_mint_address,
+        Some(token_b_mint_address),
+        OVERRIDE_MAKER_FEE_BPS,
+        OVERRIDE_TAKER_FEE_BPS,
+    );
+    add_program_account(&mut program_test, fee_override_address, fee_override);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction, leaving out the fee override of the mint pair
+    let mut take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    take_offer_instruction.accounts[18].pubkey = Pubkey::new_unique();
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::FeeOverrideKeyMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_takes_offer_with_fee_exemptions() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+    let taker_keypair = Keypair::new();
+
+    // Initialize a partial fee exemption of the taker
+    let (fee_exemption, fee_exemption_address) = FeeExemption::new(
+        &escrow_program::ID,
+        taker_keypair.pubkey(),
+        TAKER_FEE_DISCOUNT_BPS,
+        None,
+    );
+    add_program_account(&mut program_test, fee_exemption_address, fee_exemption);
+
+    // Initialize an offer (and its vault account) to be taken, made by a fully exempt maker
+    let (mut offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    offer.maker_fee_discount_bps = MAKER_FEE_DISCOUNT_BPS;
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker Token B token account (ATA)
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction with the fee exemption of the taker
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs {
+            has_fee_exemption: true,
+            ..Default::default()
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, fees are levied at the discounted rates
+    let fee_rates = FeeRates {
+        maker_fee_bps: MAKER_FEE_BPS,
+        taker_fee_bps: TAKER_FEE_BPS,
+    }
+    .discounted(MAKER_FEE_DISCOUNT_BPS, TAKER_FEE_DISCOUNT_BPS);
+    let token_a_fee_amount = fee_rates
+        .get_token_a_fee(TOKEN_A_OFFERED, &FeeRounding::default())
+        .unwrap();
+    assert_ne!(token_a_fee_amount, 0);
+
+    let token_a_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_a_mint_address),
+    )
+    .await;
+    let token_b_escrow_fee_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&escrow_state_address, &token_b_mint_address),
+    )
+    .await;
+    assert_eq!(token_a_escrow_fee_balance_after_take, token_a_fee_amount);
+    assert_eq!(token_b_escrow_fee_balance_after_take, 0);
+
+    let maker_token_b_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&maker_pubkey, &token_b_mint_address),
+    )
+    .await;
+    assert_eq!(maker_token_b_balance_after_take, TOKEN_B_WANTED);
+}
+
+#[tokio::test]
+async fn it_fails_to_take_offer_with_fee_exemption_of_other_wallet() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+    let taker_keypair = Keypair::new();
+
+    // Initialize a fee exemption of another wallet than the taker
+    let (fee_exemption, fee_exemption_address) =
+        FeeExemption::new(&escrow_program::ID, maker_pubkey, MAX_BPS_VALUE, None);
+    add_program_account(&mut program_test, fee_exemption_address, fee_exemption);
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker Token B token account (ATA)
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction with the fee exemption of the other wallet
+    let mut take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs {
+            has_fee_exemption: true,
+            ..Default::default()
+        },
+    );
+    take_offer_instruction.accounts[22].pubkey = fee_exemption_address;
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::FeeExemptionWalletMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_takes_offer_with_lamport_fee() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account, with a take lamport fee
+    let (mut escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    escrow_state.take_lamport_fee = TAKE_LAMPORT_FEE;
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize the lamport fee vault
+    let (lamport_fee_vault, lamport_fee_vault_address) = LamportFeeVault::new(&escrow_program::ID);
+    add_program_account(
+        &mut program_test,
+        lamport_fee_vault_address,
+        lamport_fee_vault,
+    );
+
+    // Create maker pubkey, and pubkey of the original funder of the offer
+    let maker_pubkey = Pubkey::new_unique();
+    let rent_payer_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        rent_payer_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction, paying the take lamport fee
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &rent_payer_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the funding account paid the take lamport fee into the vault
+    let lamport_fee_vault_lamports = banks_client
+        .get_balance(lamport_fee_vault_address)
+        .await
+        .unwrap();
+    assert_eq!(
+        lamport_fee_vault_lamports,
+        Rent::default().minimum_balance(LamportFeeVault::LEN) + TAKE_LAMPORT_FEE
+    );
+
+    let taker_token_a_balance_after_take = get_token_balance(
+        &banks_client,
+        get_associated_token_address(&taker_keypair.pubkey(), &token_a_mint_address),
+    )
+    .await;
+    assert_ne!(taker_token_a_balance_after_take, 0);
+}
+
+#[tokio::test]
+async fn it_fails_to_take_expired_offer() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an expired offer (and its vault account)
+    let (mut offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    offer.expires_at = Some(EXPIRES_AT);
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::OfferExpired as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_take_offer_when_paused() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account, with taking offers paused
+    let (mut escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    escrow_state.is_take_paused = true;
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::TakePaused as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_take_offer_partially_when_paused() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account, with taking offers paused
+    let (mut escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    escrow_state.is_take_paused = true;
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call partial take offer instruction
+    let partial_take_offer_instruction = partial_take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        PartialTakeOfferArgs {
+            token_a_amount: TOKEN_A_PARTIALLY_TAKEN,
+            taker_allowlist_proof: vec![],
+            max_token_b_to_pay: None,
+            min_token_a_to_receive: None,
+            has_fee_exemption: false,
+            referrer: None,
+        },
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[partial_take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::TakePaused as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_take_private_offer_by_other_taker() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize a private offer (and its vault account) for another taker
+    let (mut offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    offer.allowed_taker = Some(Pubkey::new_unique());
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::TakerKeyUnauthorized as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_take_offer_with_other_token_b_mint() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints, and another token B mint not wanted by the offer
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+    let other_token_b_mint_address =
+        add_mint_account(&mut program_test, TOKEN_2022_DECIMALS, spl_token::ID);
+
+    // Initialize the escrow state account
+    let (escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and other token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &other_token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        other_token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction, paying with the other token B
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &other_token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::MintKeyMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_takes_allowlisted_offer_with_proof() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Create taker keypair, and the taker allowlist including it
+    let taker_keypair = Keypair::new();
+    let allowed_takers = [
+        Pubkey::new_unique(),
+        taker_keypair.pubkey(),
+        Pubkey::new_unique(),
+    ];
+
+    // Initialize an allowlisted offer (and its vault account)
+    let (mut offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    offer.taker_allowlist_root = merkle::get_root(&allowed_takers);
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker Token B token account (ATA)
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction without the taker proof
+    let take_offer_instruction = |take_offer_args| {
+        take_offer_ix(
+            &offer_address,
+            &token_a_mint_address,
+            &token_b_mint_address,
+            &spl_token::ID,
+            &spl_token::ID,
+            &taker_token_b_account_pubkey,
+            &maker_pubkey,
+            &taker_keypair.pubkey(),
+            &maker_pubkey,
+            &payer_keypair.pubkey(),
+            take_offer_args,
+        )
+    };
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction(TakeOfferArgs::default())],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::TakerKeyUnauthorized as u32)
+        )
+    );
+
+    // Call take offer instruction with the taker proof
+    let taker_allowlist_proof =
+        merkle::get_proof(&allowed_takers, &taker_keypair.pubkey()).unwrap();
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction(TakeOfferArgs {
+                taker_allowlist_proof,
+                ..Default::default()
+            })],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the offer is closed
+    assert!(banks_client
+        .get_account(offer_address)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn it_takes_offer_within_slippage_limits() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    let token_a_fee_amount = escrow_state.get_token_a_fee(TOKEN_A_OFFERED).unwrap();
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Create taker keypair
+    let taker_keypair = Keypair::new();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker Token B token account (ATA)
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction with the exact amounts as limits
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs {
+            max_token_b_to_pay: Some(TOKEN_B_WANTED),
+            min_token_a_to_receive: Some(TOKEN_A_OFFERED - token_a_fee_amount),
+            ..Default::default()
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the offer is closed
+    assert!(banks_client
+        .get_account(offer_address)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn it_fails_to_take_offer_paying_more_token_b_than_maximum() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Create taker keypair
+    let taker_keypair = Keypair::new();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    let token_b_partially_paid = offer.get_token_b_amount(TOKEN_A_PARTIALLY_TAKEN).unwrap();
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker Token B token account (ATA)
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction with a maximum token B amount below the offer
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_ix(
+                &offer_address,
+                &token_a_mint_address,
+                &token_b_mint_address,
+                &spl_token::ID,
+                &spl_token::ID,
+                &taker_token_b_account_pubkey,
+                &maker_pubkey,
+                &taker_keypair.pubkey(),
+                &maker_pubkey,
+                &payer_keypair.pubkey(),
+                TakeOfferArgs {
+                    max_token_b_to_pay: Some(TOKEN_B_WANTED - 1),
+                    ..Default::default()
+                },
+            )],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::SlippageExceeded as u32)
+        )
+    );
+
+    // Call partial take offer instruction with a maximum token B amount below the pro-rata amount
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[partial_take_offer_ix(
+                &offer_address,
+                &token_a_mint_address,
+                &token_b_mint_address,
+                &spl_token::ID,
+                &spl_token::ID,
+                &taker_token_b_account_pubkey,
+                &maker_pubkey,
+                &taker_keypair.pubkey(),
+                &maker_pubkey,
+                &payer_keypair.pubkey(),
+                PartialTakeOfferArgs {
+                    token_a_amount: TOKEN_A_PARTIALLY_TAKEN,
+                    taker_allowlist_proof: vec![],
+                    max_token_b_to_pay: Some(token_b_partially_paid - 1),
+                    min_token_a_to_receive: None,
+                    has_fee_exemption: false,
+                    referrer: None,
+                },
+            )],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::SlippageExceeded as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_take_offer_receiving_less_token_a_than_minimum() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    let token_a_fee_amount = escrow_state.get_token_a_fee(TOKEN_A_OFFERED).unwrap();
+    let token_a_partial_fee_amount = escrow_state
+        .get_token_a_fee(TOKEN_A_PARTIALLY_TAKEN)
+        .unwrap();
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Create taker keypair
+    let taker_keypair = Keypair::new();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker Token B token account (ATA)
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction with a minimum token A amount above the offer
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_ix(
+                &offer_address,
+                &token_a_mint_address,
+                &token_b_mint_address,
+                &spl_token::ID,
+                &spl_token::ID,
+                &taker_token_b_account_pubkey,
+                &maker_pubkey,
+                &taker_keypair.pubkey(),
+                &maker_pubkey,
+                &payer_keypair.pubkey(),
+                TakeOfferArgs {
+                    min_token_a_to_receive: Some(TOKEN_A_OFFERED - token_a_fee_amount + 1),
+                    ..Default::default()
+                },
+            )],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::SlippageExceeded as u32)
+        )
+    );
+
+    // Call partial take offer instruction with a minimum token A amount above the amount taken
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[partial_take_offer_ix(
+                &offer_address,
+                &token_a_mint_address,
+                &token_b_mint_address,
+                &spl_token::ID,
+                &spl_token::ID,
+                &taker_token_b_account_pubkey,
+                &maker_pubkey,
+                &taker_keypair.pubkey(),
+                &maker_pubkey,
+                &payer_keypair.pubkey(),
+                PartialTakeOfferArgs {
+                    token_a_amount: TOKEN_A_PARTIALLY_TAKEN,
+                    taker_allowlist_proof: vec![],
+                    max_token_b_to_pay: None,
+                    min_token_a_to_receive: Some(
+                        TOKEN_A_PARTIALLY_TAKEN - token_a_partial_fee_amount + 1,
+                    ),
+                    has_fee_exemption: false,
+                    referrer: None,
+                },
+            )],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::SlippageExceeded as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_takes_offer_with_token_2022() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints (token A on Token-2022, token B on SPL Token)
+    let token_a_mint_address =
+        add_mint_account(&mut program_test, TOKEN_2022_DECIMALS, spl_token_2022::ID);
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address_with_program_id(
+        &offer_address,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_program_id(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+        spl_token_2022::ID,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token_2022::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+    let token_a_fee_amount = escrow_state.get_token_a_fee(TOKEN_A_OFFERED).unwrap();
+
+    let token_a_escrow_fee_address = get_associated_token_address_with_program_id(
+        &escrow_state_address,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, token_a_escrow_fee_address).await,
+        token_a_fee_amount
+    );
+
+    let taker_token_a_account_pubkey = get_associated_token_address_with_program_id(
+        &taker_keypair.pubkey(),
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, taker_token_a_account_pubkey).await,
+        TOKEN_A_OFFERED - token_a_fee_amount
+    );
+
+    assert!(banks_client
+        .get_account(vault_address)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn it_takes_offer_with_transfer_fee() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints (token A on Token-2022 with transfer fee, token B on SPL Token)
+    let token_a_mint_address = add_mint_account_with_transfer_fee(
+        &mut program_test,
+        TOKEN_2022_DECIMALS,
+        TRANSFER_FEE_BPS,
+    );
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account, with withheld transfer fees) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address_with_program_id(
+        &offer_address,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_fee_amount(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+        VAULT_WITHHELD_AMOUNT,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token_2022::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the escrow fee account receives the exact fee, despite the transfer fee
+    let escrow_state =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+    let token_a_fee_amount = escrow_state.get_token_a_fee(TOKEN_A_OFFERED).unwrap();
+
+    let token_a_escrow_fee_address = get_associated_token_address_with_program_id(
+        &escrow_state_address,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, token_a_escrow_fee_address).await,
+        token_a_fee_amount
+    );
+
+    // Check the vault is closed, with its withheld transfer fees harvested to the mint
+    assert!(banks_client
+        .get_account(vault_address)
+        .await
+        .unwrap()
+        .is_none());
+
+    let token_a_mint_account = banks_client
+        .get_account(token_a_mint_address)
+        .await
+        .unwrap()
+        .unwrap();
+    let token_a_mint = StateWithExtensions::<Mint>::unpack(&token_a_mint_account.data).unwrap();
+    let withheld_amount: u64 = token_a_mint
+        .get_extension::<TransferFeeConfig>()
+        .unwrap()
+        .withheld_amount
+        .into();
+    assert_eq!(withheld_amount, VAULT_WITHHELD_AMOUNT);
+}
+
+#[tokio::test]
+async fn it_takes_offer_with_transfer_hook() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+    add_transfer_hook_program(&mut program_test);
+
+    // Load token mints (token A on Token-2022 with transfer hook, token B on SPL Token)
+    let token_a_mint_address =
+        add_mint_account_with_transfer_hook(&mut program_test, TOKEN_2022_DECIMALS);
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    let maker_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address_with_program_id(
+        &offer_address,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_hook_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair, and Token B token account (ATA)
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction with the transfer hook accounts resolved
+    let take_offer_instruction = take_offer_ix_with_extra_metas(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token_2022::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &maker_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+        |address| {
+            let banks_client = banks_client.clone();
+            async move {
+                let account = banks_client.get_account(address).await?;
+                Ok::<_, AccountFetchError>(account.map(|account| account.data))
+            }
+        },
+    )
+    .await
+    .unwrap();
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+    let token_a_fee_amount = escrow_state.get_token_a_fee(TOKEN_A_OFFERED).unwrap();
+
+    let taker_token_a_account_pubkey = get_associated_token_address_with_program_id(
+        &taker_keypair.pubkey(),
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, taker_token_a_account_pubkey).await,
+        TOKEN_A_OFFERED - token_a_fee_amount
+    );
+
+    assert!(banks_client
+        .get_account(vault_address)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn it_takes_offer_for_native_sol() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token A mint (token B is native SOL, wrapped from the taker's wallet)
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = spl_token::native_mint::ID;
+
+    // Initialize the escrow state account
+    let (escrow_state, escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Create maker wallet, receiving SOL directly, and pubkey of the original funder of the offer
+    let maker_pubkey = Pubkey::new_unique();
+    let rent_payer_pubkey = Pubkey::new_unique();
+    add_wallet_account(&mut program_test, maker_pubkey, WALLET_LAMPORTS);
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        rent_payer_pubkey,
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // Create taker keypair with SOL, without any token B token account
+    let taker_keypair = Keypair::new();
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_wallet_account(&mut program_test, taker_keypair.pubkey(), WALLET_LAMPORTS);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call take offer instruction
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &rent_payer_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let escrow_state =
+        get_program_account::<EscrowState>(&banks_client, escrow_state_address).await;
+    let token_a_fee_amount = escrow_state.get_token_a_fee(TOKEN_A_OFFERED).unwrap();
+    let token_b_fee_amount = escrow_state.get_token_b_fee(TOKEN_B_WANTED).unwrap();
+
+    let token_b_escrow_fee_address =
+        get_associated_token_address(&escrow_state_address, &token_b_mint_address);
+    let token_b_escrow_fee_balance_after_take =
+        get_token_balance(&banks_client, token_b_escrow_fee_address).await;
+    assert_eq!(token_b_escrow_fee_balance_after_take, token_b_fee_amount);
+
+    let taker_token_a_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_a_mint_address);
+    let taker_token_a_balance_after_take =
+        get_token_balance(&banks_client, taker_token_a_account_pubkey).await;
+    assert_eq!(
+        taker_token_a_balance_after_take,
+        TOKEN_A_OFFERED - token_a_fee_amount
+    );
+
+    let taker_lamports_after_take = banks_client
+        .get_balance(taker_keypair.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(taker_lamports_after_take, WALLET_LAMPORTS - TOKEN_B_WANTED);
+
+    let maker_lamports_after_take = banks_client.get_balance(maker_pubkey).await.unwrap();
+    assert_eq!(
+        maker_lamports_after_take,
+        WALLET_LAMPORTS + TOKEN_B_WANTED - token_b_fee_amount
+    );
+}
+
+#[tokio::test]
+async fn it_takes_offer_of_native_sol_partially() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token B mint (token A is native SOL, sent from the vault to the taker's wallet)
+    let token_a_mint_address = spl_token::native_mint::ID;
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Initialize the escrow state account
+    let (escrow_state, _escrow_state_address) = EscrowState::new(
+        &escrow_program::ID,
+        Pubkey::new_unique(),
+        MAKER_FEE_BPS,
+        TAKER_FEE_BPS,
+    );
+    let token_a_fee_amount_on_partial_take = escrow_state
+        .get_token_a_fee(TOKEN_A_PARTIALLY_TAKEN)
+        .unwrap();
+    let token_a_fee_amount_on_final_take = escrow_state
+        .get_token_a_fee(TOKEN_A_OFFERED - TOKEN_A_PARTIALLY_TAKEN)
+        .unwrap();
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Create maker pubkey, and pubkey of the original funder of the offer
+    let maker_pubkey = Pubkey::new_unique();
+    let rent_payer_pubkey = Pubkey::new_unique();
+
+    // Initialize an offer (and its vault account) to be taken
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_pubkey,
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        rent_payer_pubkey,
+    );
+    let offer_rent = Rent::default().minimum_balance(Offer::LEN);
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_native_token_account(
+        &mut program_test,
+        vault_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+    let vault_rent = Rent::default().minimum_balance(TokenAccount::LEN);
+
+    // Create taker keypair with SOL, token B token account (ATA), and a funded token A token
+    // account (ATA) which should be left untouched
+    let taker_keypair = Keypair::new();
+    add_wallet_account(&mut program_test, taker_keypair.pubkey(), WALLET_LAMPORTS);
+    let taker_token_b_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_b_mint_address);
+    add_token_account(
+        &mut program_test,
+        taker_token_b_account_pubkey,
+        token_b_mint_address,
+        taker_keypair.pubkey(),
+        TAKER_TOKEN_B_BALANCE,
+    );
+    let taker_token_a_account_pubkey =
+        get_associated_token_address(&taker_keypair.pubkey(), &token_a_mint_address);
+    add_native_token_account(
+        &mut program_test,
+        taker_token_a_account_pubkey,
+        taker_keypair.pubkey(),
+        TAKER_WSOL_BALANCE,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call partial take offer instruction
+    let partial_take_offer_instruction = partial_take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &rent_payer_pubkey,
+        &payer_keypair.pubkey(),
+        PartialTakeOfferArgs {
+            token_a_amount: TOKEN_A_PARTIALLY_TAKEN,
+            taker_allowlist_proof: vec![],
+            max_token_b_to_pay: None,
+            min_token_a_to_receive: None,
+            has_fee_exemption: false,
+            referrer: None,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[partial_take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the vault keeps the rest of SOL (on top of its rent) after the partial take
+    let vault_balance_after_partial_take = get_token_balance(&banks_client, vault_address).await;
+    assert_eq!(
+        vault_balance_after_partial_take,
+        TOKEN_A_OFFERED - TOKEN_A_PARTIALLY_TAKEN
+    );
+
+    let vault_lamports_after_partial_take = banks_client.get_balance(vault_address).await.unwrap();
+    assert_eq!(
+        vault_lamports_after_partial_take,
+        vault_rent + TOKEN_A_OFFERED - TOKEN_A_PARTIALLY_TAKEN
+    );
+
+    let taker_lamports_after_partial_take = banks_client
+        .get_balance(taker_keypair.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        taker_lamports_after_partial_take,
+        WALLET_LAMPORTS + TOKEN_A_PARTIALLY_TAKEN - token_a_fee_amount_on_partial_take
+    );
+
+    // Take the rest of the offer
+    let take_offer_instruction = take_offer_ix(
+        &offer_address,
+        &token_a_mint_address,
+        &token_b_mint_address,
+        &spl_token::ID,
+        &spl_token::ID,
+        &taker_token_b_account_pubkey,
+        &maker_pubkey,
+        &taker_keypair.pubkey(),
+        &rent_payer_pubkey,
+        &payer_keypair.pubkey(),
+        TakeOfferArgs::default(),
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[take_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &taker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the taker receives SOL in its wallet
+    let taker_lamports_after_take = banks_client
+        .get_balance(taker_keypair.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        taker_lamports_after_take,
+        WALLET_LAMPORTS + TOKEN_A_OFFERED
+            - token_a_fee_amount_on_partial_take
+            - token_a_fee_amount_on_final_take
+    );
+
+    let taker_token_a_balance_after_take =
+        get_token_balance(&banks_client, taker_token_a_account_pubkey).await;
+    assert_eq!(taker_token_a_balance_after_take, TAKER_WSOL_BALANCE);
+
+    let rent_payer_balance_after_take = banks_client.get_balance(rent_payer_pubkey).await.unwrap();
+    assert_eq!(rent_payer_balance_after_take, offer_rent + vault_rent);
+
+    assert!(banks_client
+        .get_account(offer_address)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(banks_client
+        .get_account(vault_address)
+        .await
+        .unwrap()
+        .is_none());
 }
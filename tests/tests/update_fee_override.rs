@@ -0,0 +1,68 @@
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+
+use escrow_program::{
+    instructions::{update_fee_override::UpdateFeeOverrideArgs, update_fee_override_ix},
+    state::{EscrowState, FeeOverride},
+};
+
+use crate::utils::{
+    add_escrow_state_account, add_program_account, get_program_account, prepare_program_test,
+};
+
+const ORIG_MAKER_FEE_BPS: u16 = 0;
+const ORIG_TAKER_FEE_BPS: u16 = 0;
+const MAKER_FEE_BPS: u16 = 1337;
+const TAKER_FEE_BPS: u16 = 420;
+
+#[tokio::test]
+async fn it_updates_fee_override() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Initialize the escrow state account
+    let manager_keypair = Keypair::new();
+    let (escrow_state, _) = EscrowState::new(&escrow_program::ID, manager_keypair.pubkey(), 0, 0);
+    add_escrow_state_account(&mut program_test, escrow_state);
+
+    // Initialize a fee override for a mint pair
+    let token_a_mint_address = Pubkey::new_unique();
+    let token_b_mint_address = Pubkey::new_unique();
+    let (fee_override, fee_override_address) = FeeOverride::new(
+        &escrow_program::ID,
+        token_a_mint_address,
+        Some(token_b_mint_address),
+        ORIG_MAKER_FEE_BPS,
+        ORIG_TAKER_FEE_BPS,
+    );
+    add_program_account(&mut program_test, fee_override_address, fee_override);
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call update fee override instruction
+    let update_fee_override_instruction = update_fee_override_ix(
+        &manager_keypair.pubkey(),
+        &token_a_mint_address,
+        Some(&token_b_mint_address),
+        UpdateFeeOverrideArgs {
+            maker_fee_bps: MAKER_FEE_BPS,
+            taker_fee_bps: TAKER_FEE_BPS,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[update_fee_override_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &manager_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result
+    let fee_override_after_update =
+        get_program_account::<FeeOverride>(&banks_client, fee_override_address).await;
+
+    assert_eq!(fee_override_after_update.maker_fee_bps, MAKER_FEE_BPS);
+    assert_eq!(fee_override_after_update.taker_fee_bps, TAKER_FEE_BPS);
+}
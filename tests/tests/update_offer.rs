@@ -0,0 +1,509 @@
+use solana_sdk::{
+    instruction::{AccountMeta, InstructionError},
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
+
+use escrow_program::{
+    errors::EscrowError,
+    instructions::{update_offer::UpdateOfferArgs, update_offer_ix},
+    state::Offer,
+};
+
+use crate::utils::{
+    add_mint_account_with_transfer_fee, add_program_account, add_test_fixture_from_file,
+    add_token_account, add_token_account_with_transfer_fee_amount, get_program_account,
+    get_token_balance, prepare_program_test,
+};
+
+const OFFER_ID: u64 = 0;
+const MAKER_TOKEN_A_BALANCE: u64 = 1337;
+const TOKEN_A_OFFERED: u64 = 69;
+const TOKEN_B_WANTED: u64 = 420;
+const TOKEN_B_WANTED_UPDATED: u64 = 690;
+const TOKEN_A_DEPOSITED: u64 = 31; // NB: should be lower than MAKER_TOKEN_A_BALANCE
+const TOKEN_A_WITHDRAWN: u64 = 42; // NB: should be lower than TOKEN_A_OFFERED + TOKEN_A_DEPOSITED
+const TOKEN_A_DONATED: u64 = 1;
+const TOKEN_2022_DECIMALS: u8 = 9;
+const TRANSFER_FEE_BPS: u16 = 100;
+const TOKEN_A_TRANSFER_FEE: u64 = 1; // NB: TOKEN_A_DEPOSITED * TRANSFER_FEE_BPS, rounded up
+
+#[tokio::test]
+async fn it_updates_offer() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Create maker keypair, and token A token account (ATA)
+    let maker_keypair = Keypair::new();
+    let maker_token_a_account_pubkey =
+        get_associated_token_address(&maker_keypair.pubkey(), &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        maker_token_a_account_pubkey,
+        token_a_mint_address,
+        maker_keypair.pubkey(),
+        MAKER_TOKEN_A_BALANCE,
+    );
+
+    // Initialize an offer (and its vault account) to be updated
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_keypair.pubkey(),
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_keypair.pubkey(),
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    let update_offer_instruction = |update_offer_args| {
+        update_offer_ix(
+            &maker_keypair.pubkey(),
+            &maker_token_a_account_pubkey,
+            &token_a_mint_address,
+            &spl_token::ID,
+            OFFER_ID,
+            update_offer_args,
+        )
+    };
+
+    // Call update offer instruction to reprice and deposit token A
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[update_offer_instruction(UpdateOfferArgs {
+                token_b_wanted_amount: Some(TOKEN_B_WANTED_UPDATED),
+                token_a_deposit_amount: TOKEN_A_DEPOSITED,
+                token_a_withdraw_amount: 0,
+            })],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let offer_after_deposit = get_program_account::<Offer>(&banks_client, offer_address).await;
+    assert_eq!(
+        offer_after_deposit.token_a_offered_amount,
+        TOKEN_A_OFFERED + TOKEN_A_DEPOSITED
+    );
+    assert_eq!(
+        offer_after_deposit.token_b_wanted_amount,
+        TOKEN_B_WANTED_UPDATED
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, vault_address).await,
+        TOKEN_A_OFFERED + TOKEN_A_DEPOSITED
+    );
+
+    // Call update offer instruction to withdraw token A
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[update_offer_instruction(UpdateOfferArgs {
+                token_b_wanted_amount: None,
+                token_a_deposit_amount: 0,
+                token_a_withdraw_amount: TOKEN_A_WITHDRAWN,
+            })],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    let offer_after_withdraw = get_program_account::<Offer>(&banks_client, offer_address).await;
+    assert_eq!(
+        offer_after_withdraw.token_a_offered_amount,
+        TOKEN_A_OFFERED + TOKEN_A_DEPOSITED - TOKEN_A_WITHDRAWN
+    );
+    assert_eq!(
+        offer_after_withdraw.token_b_wanted_amount,
+        TOKEN_B_WANTED_UPDATED
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, maker_token_a_account_pubkey).await,
+        MAKER_TOKEN_A_BALANCE - TOKEN_A_DEPOSITED + TOKEN_A_WITHDRAWN
+    );
+
+    // Call update offer instruction to withdraw all token A left
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[update_offer_instruction(UpdateOfferArgs {
+                token_b_wanted_amount: None,
+                token_a_deposit_amount: 0,
+                token_a_withdraw_amount: offer_after_withdraw.token_a_offered_amount,
+            })],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::InvalidWithdrawAmount as u32)
+        )
+    );
+
+    // Check the offer is left untouched
+    assert_eq!(
+        get_token_balance(&banks_client, vault_address).await,
+        offer_after_withdraw.token_a_offered_amount
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_update_offer_withdrawing_everything() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Create maker keypair, and token A token account (ATA)
+    let maker_keypair = Keypair::new();
+    let maker_token_a_account_pubkey =
+        get_associated_token_address(&maker_keypair.pubkey(), &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        maker_token_a_account_pubkey,
+        token_a_mint_address,
+        maker_keypair.pubkey(),
+        MAKER_TOKEN_A_BALANCE,
+    );
+
+    // Initialize an offer (and its vault account) to be updated
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_keypair.pubkey(),
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_keypair.pubkey(),
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call update offer instruction to withdraw all token A, with a deposit
+    let update_offer_instruction = update_offer_ix(
+        &maker_keypair.pubkey(),
+        &maker_token_a_account_pubkey,
+        &token_a_mint_address,
+        &spl_token::ID,
+        OFFER_ID,
+        UpdateOfferArgs {
+            token_b_wanted_amount: None,
+            token_a_deposit_amount: TOKEN_A_DEPOSITED,
+            token_a_withdraw_amount: TOKEN_A_OFFERED + TOKEN_A_DEPOSITED,
+        },
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[update_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::InvalidWithdrawAmount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_update_offer_by_other_signer() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Create maker keypair, and token A token account (ATA)
+    let maker_keypair = Keypair::new();
+    let maker_token_a_account_pubkey =
+        get_associated_token_address(&maker_keypair.pubkey(), &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        maker_token_a_account_pubkey,
+        token_a_mint_address,
+        maker_keypair.pubkey(),
+        MAKER_TOKEN_A_BALANCE,
+    );
+
+    // Initialize an offer (and its vault account) to be updated
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_keypair.pubkey(),
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_keypair.pubkey(),
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call update offer instruction, signed by another wallet than the maker
+    let other_keypair = Keypair::new();
+    let mut update_offer_instruction = update_offer_ix(
+        &maker_keypair.pubkey(),
+        &maker_token_a_account_pubkey,
+        &token_a_mint_address,
+        &spl_token::ID,
+        OFFER_ID,
+        UpdateOfferArgs {
+            token_b_wanted_amount: None,
+            token_a_deposit_amount: 0,
+            token_a_withdraw_amount: TOKEN_A_WITHDRAWN,
+        },
+    );
+    update_offer_instruction.accounts[4] = AccountMeta::new_readonly(other_keypair.pubkey(), true);
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[update_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &other_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::MakerKeyMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_fails_to_update_offer_withdrawing_deposit_before_transfer_fee() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints (token A on Token-2022 with transfer fee, token B on SPL Token)
+    let token_a_mint_address = add_mint_account_with_transfer_fee(
+        &mut program_test,
+        TOKEN_2022_DECIMALS,
+        TRANSFER_FEE_BPS,
+    );
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Create maker keypair, and token A token account (ATA)
+    let maker_keypair = Keypair::new();
+    let maker_token_a_account_pubkey = get_associated_token_address_with_program_id(
+        &maker_keypair.pubkey(),
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_fee_amount(
+        &mut program_test,
+        maker_token_a_account_pubkey,
+        token_a_mint_address,
+        maker_keypair.pubkey(),
+        MAKER_TOKEN_A_BALANCE,
+        0,
+    );
+
+    // Initialize an offer (and its vault account) to be updated
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_keypair.pubkey(),
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_keypair.pubkey(),
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address_with_program_id(
+        &offer_address,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+    );
+    add_token_account_with_transfer_fee_amount(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED,
+        0,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call update offer instruction to withdraw all token A escrowed after the transfer fee
+    let update_offer_instruction = update_offer_ix(
+        &maker_keypair.pubkey(),
+        &maker_token_a_account_pubkey,
+        &token_a_mint_address,
+        &spl_token_2022::ID,
+        OFFER_ID,
+        UpdateOfferArgs {
+            token_b_wanted_amount: None,
+            token_a_deposit_amount: TOKEN_A_DEPOSITED,
+            token_a_withdraw_amount: TOKEN_A_OFFERED + TOKEN_A_DEPOSITED - TOKEN_A_TRANSFER_FEE,
+        },
+    );
+    let err = banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[update_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap_err()
+        .unwrap();
+
+    // Check the result
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(EscrowError::InvalidWithdrawAmount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn it_updates_offer_with_extra_token_in_vault() {
+    // [Setup Test]
+    let mut program_test = prepare_program_test();
+
+    // Load token mints
+    let token_a_mint_address = add_test_fixture_from_file(&mut program_test, "inf-mint.json");
+    let token_b_mint_address = add_test_fixture_from_file(&mut program_test, "usdc-mint.json");
+
+    // Create maker keypair, and token A token account (ATA)
+    let maker_keypair = Keypair::new();
+    let maker_token_a_account_pubkey =
+        get_associated_token_address(&maker_keypair.pubkey(), &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        maker_token_a_account_pubkey,
+        token_a_mint_address,
+        maker_keypair.pubkey(),
+        MAKER_TOKEN_A_BALANCE,
+    );
+
+    // Initialize an offer (and its vault account, with an extra token sent to it) to be updated
+    let (offer, offer_address) = Offer::new(
+        &escrow_program::ID,
+        OFFER_ID,
+        maker_keypair.pubkey(),
+        token_a_mint_address,
+        token_b_mint_address,
+        TOKEN_A_OFFERED,
+        TOKEN_B_WANTED,
+        maker_keypair.pubkey(),
+    );
+    add_program_account(&mut program_test, offer_address, offer);
+    let vault_address = get_associated_token_address(&offer_address, &token_a_mint_address);
+    add_token_account(
+        &mut program_test,
+        vault_address,
+        token_a_mint_address,
+        offer_address,
+        TOKEN_A_OFFERED + TOKEN_A_DONATED,
+    );
+
+    // [Start Test]
+    let (banks_client, payer_keypair, last_blockhash) = program_test.start().await;
+
+    // Call update offer instruction to deposit and withdraw token A
+    let update_offer_instruction = update_offer_ix(
+        &maker_keypair.pubkey(),
+        &maker_token_a_account_pubkey,
+        &token_a_mint_address,
+        &spl_token::ID,
+        OFFER_ID,
+        UpdateOfferArgs {
+            token_b_wanted_amount: None,
+            token_a_deposit_amount: TOKEN_A_DEPOSITED,
+            token_a_withdraw_amount: TOKEN_A_WITHDRAWN,
+        },
+    );
+    banks_client
+        .process_transaction(Transaction::new_signed_with_payer(
+            &[update_offer_instruction],
+            Some(&payer_keypair.pubkey()),
+            &[&payer_keypair, &maker_keypair],
+            last_blockhash,
+        ))
+        .await
+        .unwrap();
+
+    // Check the result, the extra token is left in the vault
+    let offer_after_update = get_program_account::<Offer>(&banks_client, offer_address).await;
+    assert_eq!(
+        offer_after_update.token_a_offered_amount,
+        TOKEN_A_OFFERED + TOKEN_A_DEPOSITED - TOKEN_A_WITHDRAWN
+    );
+    assert_eq!(
+        get_token_balance(&banks_client, vault_address).await,
+        TOKEN_A_OFFERED + TOKEN_A_DEPOSITED - TOKEN_A_WITHDRAWN + TOKEN_A_DONATED
+    );
+}
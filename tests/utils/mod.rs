@@ -1,5 +1,7 @@
 pub mod program_test;
 pub mod test_fixture;
+pub mod transfer_hook;
 
 pub use program_test::*;
 pub use test_fixture::*;
+pub use transfer_hook::*;
@@ -1,11 +1,18 @@
 use std::path::Path;
 
-use escrow_program::state::{EscrowState, Offer};
-use solana_program_test::{processor, ProgramTest};
+use borsh::{BorshDeserialize, BorshSerialize};
+use escrow_program::state::{EscrowState, LegacyEscrowState, LegacyOffer, Offer, ProgramAccount};
+use solana_program_test::{processor, BanksClient, ProgramTest};
 use solana_sdk::{
     account::Account, program_option::COption, program_pack::Pack, pubkey::Pubkey, rent::Rent,
 };
-use spl_token::state::{Account as TokenAccount, AccountState as TokenAccountState};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{TransferFee, TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensions, StateWithExtensionsMut,
+    },
+    state::{Account as TokenAccount, AccountState as TokenAccountState, Mint},
+};
 
 use super::TestFixture;
 
@@ -22,7 +29,7 @@ pub fn prepare_program_test() -> ProgramTest {
 pub fn add_escrow_state_account(program_test: &mut ProgramTest, escrow_state: EscrowState) {
     let address =
         EscrowState::create_program_address(&escrow_program::ID, escrow_state.bump).unwrap();
-    let data = borsh::to_vec::<EscrowState>(&escrow_state).unwrap();
+    let data = program_account_data(&escrow_state);
     let lamports = Rent::default().minimum_balance(data.len());
     let account = Account {
         lamports,
@@ -35,12 +42,79 @@ pub fn add_escrow_state_account(program_test: &mut ProgramTest, escrow_state: Es
     program_test.add_account(address, account);
 }
 
-/// Load given Offer into ProgramTest.
-pub fn add_offer_account(program_test: &mut ProgramTest, offer: Offer) {
+/// Load given legacy (unversioned) EscrowState into ProgramTest.
+pub fn add_legacy_escrow_state_account(
+    program_test: &mut ProgramTest,
+    escrow_state: LegacyEscrowState,
+) {
+    let address =
+        EscrowState::create_program_address(&escrow_program::ID, escrow_state.bump).unwrap();
+    add_legacy_program_account(program_test, address, &escrow_state);
+}
+
+/// Load given legacy (unversioned) Offer into ProgramTest.
+pub fn add_legacy_offer_account(program_test: &mut ProgramTest, offer: LegacyOffer) {
     let address =
         Offer::create_program_address(&escrow_program::ID, &offer.maker, offer.id, offer.bump)
             .unwrap();
-    let data = borsh::to_vec::<Offer>(&offer).unwrap();
+    add_legacy_program_account(program_test, address, &offer);
+}
+
+/// Legacy accounts are sized exactly to their serialized data.
+fn add_legacy_program_account<T: BorshSerialize>(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    legacy_account: &T,
+) {
+    let data = borsh::to_vec(legacy_account).unwrap();
+    let lamports = Rent::default().minimum_balance(data.len());
+    let account = Account {
+        lamports,
+        data,
+        owner: escrow_program::ID,
+        executable: false,
+        rent_epoch: u64::MAX,
+    };
+
+    program_test.add_account(address, account);
+}
+
+/// Serialize given program account, padded with its reserved bytes.
+fn program_account_data<T: ProgramAccount>(program_account: &T) -> Vec<u8> {
+    let mut data = vec![0u8; T::LEN];
+    program_account.serialize(&mut &mut data[..]).unwrap();
+
+    data
+}
+
+/// Fetch and deserialize a program account, ignoring its reserved bytes.
+pub async fn get_program_account<T: BorshDeserialize>(
+    banks_client: &BanksClient,
+    address: Pubkey,
+) -> T {
+    let account = banks_client.get_account(address).await.unwrap().unwrap();
+
+    T::deserialize(&mut &account.data[..]).unwrap()
+}
+
+/// Load a system account (wallet) holding the given lamports into ProgramTest.
+pub fn add_wallet_account(program_test: &mut ProgramTest, address: Pubkey, lamports: u64) {
+    let account = Account {
+        lamports,
+        owner: solana_sdk::system_program::ID,
+        ..Account::default()
+    };
+
+    program_test.add_account(address, account);
+}
+
+/// Load given program account (rent-exempt) at the given address into ProgramTest.
+pub fn add_program_account<T: ProgramAccount>(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    program_account: T,
+) {
+    let data = program_account_data(&program_account);
     let lamports = Rent::default().minimum_balance(data.len());
     let account = Account {
         lamports,
@@ -59,6 +133,25 @@ pub fn add_token_account(
     mint_pubkey: Pubkey,
     owner_pubkey: Pubkey,
     balance: u64,
+) {
+    add_token_account_with_program_id(
+        program_test,
+        address,
+        mint_pubkey,
+        owner_pubkey,
+        balance,
+        spl_token::ID,
+    );
+}
+
+/// Load a token account owned by the given token program (SPL Token or Token-2022).
+pub fn add_token_account_with_program_id(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    mint_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+    balance: u64,
+    token_program_id: Pubkey,
 ) {
     let token_account = TokenAccount {
         mint: mint_pubkey,
@@ -76,6 +169,37 @@ pub fn add_token_account(
     let account = Account {
         lamports,
         data,
+        owner: token_program_id,
+        executable: false,
+        rent_epoch: u64::MAX,
+    };
+
+    program_test.add_account(address, account);
+}
+
+/// Load a native (wrapped SOL) SPL Token account, holding the balance on top of its rent.
+pub fn add_native_token_account(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    owner_pubkey: Pubkey,
+    balance: u64,
+) {
+    let rent = Rent::default().minimum_balance(TokenAccount::LEN);
+    let token_account = TokenAccount {
+        mint: spl_token::native_mint::ID,
+        owner: owner_pubkey,
+        amount: balance,
+        delegate: COption::None,
+        state: TokenAccountState::Initialized,
+        is_native: COption::Some(rent),
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    let mut data = vec![0u8; TokenAccount::LEN];
+    token_account.pack_into_slice(&mut data);
+    let account = Account {
+        lamports: rent + balance,
+        data,
         owner: spl_token::ID,
         executable: false,
         rent_epoch: u64::MAX,
@@ -84,6 +208,138 @@ pub fn add_token_account(
     program_test.add_account(address, account);
 }
 
+/// Load a mint (without extensions) owned by the given token program.
+///
+/// Returns address of the mint account.
+pub fn add_mint_account(
+    program_test: &mut ProgramTest,
+    decimals: u8,
+    token_program_id: Pubkey,
+) -> Pubkey {
+    let address = Pubkey::new_unique();
+    let mint = Mint {
+        mint_authority: COption::None,
+        supply: u64::MAX,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    let mut data = vec![0u8; Mint::LEN];
+    mint.pack_into_slice(&mut data);
+    let lamports = Rent::default().minimum_balance(data.len());
+    let account = Account {
+        lamports,
+        data,
+        owner: token_program_id,
+        executable: false,
+        rent_epoch: u64::MAX,
+    };
+
+    program_test.add_account(address, account);
+
+    address
+}
+
+/// Load a Token-2022 mint with the TransferFee extension.
+///
+/// Returns address of the mint account.
+pub fn add_mint_account_with_transfer_fee(
+    program_test: &mut ProgramTest,
+    decimals: u8,
+    transfer_fee_basis_points: u16,
+) -> Pubkey {
+    let address = Pubkey::new_unique();
+    let transfer_fee = TransferFee {
+        epoch: 0.into(),
+        maximum_fee: u64::MAX.into(),
+        transfer_fee_basis_points: transfer_fee_basis_points.into(),
+    };
+
+    let space =
+        ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+            .unwrap();
+    let mut data = vec![0u8; space];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    let extension = state.init_extension::<TransferFeeConfig>(true).unwrap();
+    extension.older_transfer_fee = transfer_fee;
+    extension.newer_transfer_fee = transfer_fee;
+    state.base = Mint {
+        mint_authority: COption::None,
+        supply: u64::MAX,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+
+    let lamports = Rent::default().minimum_balance(data.len());
+    let account = Account {
+        lamports,
+        data,
+        owner: spl_token_2022::ID,
+        executable: false,
+        rent_epoch: u64::MAX,
+    };
+
+    program_test.add_account(address, account);
+
+    address
+}
+
+/// Load a Token-2022 token account with the TransferFeeAmount extension.
+pub fn add_token_account_with_transfer_fee_amount(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    mint_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+    balance: u64,
+    withheld_amount: u64,
+) {
+    let space = ExtensionType::try_calculate_account_len::<TokenAccount>(&[
+        ExtensionType::TransferFeeAmount,
+    ])
+    .unwrap();
+    let mut data = vec![0u8; space];
+    let mut state =
+        StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+    let extension = state.init_extension::<TransferFeeAmount>(true).unwrap();
+    extension.withheld_amount = withheld_amount.into();
+    state.base = TokenAccount {
+        mint: mint_pubkey,
+        owner: owner_pubkey,
+        amount: balance,
+        delegate: COption::None,
+        state: TokenAccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+
+    let lamports = Rent::default().minimum_balance(data.len());
+    let account = Account {
+        lamports,
+        data,
+        owner: spl_token_2022::ID,
+        executable: false,
+        rent_epoch: u64::MAX,
+    };
+
+    program_test.add_account(address, account);
+}
+
+/// Read the balance of an SPL Token or Token-2022 account (with extensions).
+pub async fn get_token_balance(banks_client: &BanksClient, address: Pubkey) -> u64 {
+    let account = banks_client.get_account(address).await.unwrap().unwrap();
+
+    StateWithExtensions::<TokenAccount>::unpack(&account.data)
+        .unwrap()
+        .base
+        .amount
+}
+
 /// Load given test fixture file into ProgramTest.
 ///
 /// Returns address of the account created from the given test fixture.
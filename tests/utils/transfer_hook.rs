@@ -0,0 +1,139 @@
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::{
+    account::Account, account_info::AccountInfo, entrypoint::ProgramResult,
+    program_error::ProgramError, program_option::COption, pubkey::Pubkey, rent::Rent,
+};
+use spl_pod::optional_keys::OptionalNonZeroPubkey;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_token_2022::{
+    extension::{
+        transfer_hook::{TransferHook, TransferHookAccount},
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+    },
+    state::{Account as TokenAccount, AccountState as TokenAccountState, Mint},
+};
+use spl_transfer_hook_interface::{
+    get_extra_account_metas_address, instruction::ExecuteInstruction,
+};
+
+pub const TRANSFER_HOOK_PROGRAM_ID: Pubkey = Pubkey::new_from_array([1u8; 32]);
+
+/// Extra account required by the test transfer hook on every transfer.
+pub const TRANSFER_HOOK_EXTRA_ACCOUNT: Pubkey = Pubkey::new_from_array([2u8; 32]);
+
+/// Test transfer hook, which only checks it receives its extra account.
+///
+/// Expects accounts: source, mint, destination, authority, validation state, extra account.
+fn process_transfer_hook(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    match accounts.get(5) {
+        Some(extra_account) if *extra_account.key == TRANSFER_HOOK_EXTRA_ACCOUNT => Ok(()),
+        _ => Err(ProgramError::NotEnoughAccountKeys),
+    }
+}
+
+/// Load the test transfer hook program into ProgramTest.
+pub fn add_transfer_hook_program(program_test: &mut ProgramTest) {
+    program_test.add_program(
+        "transfer_hook",
+        TRANSFER_HOOK_PROGRAM_ID,
+        processor!(process_transfer_hook),
+    );
+}
+
+/// Load a Token-2022 mint with the TransferHook extension pointing to the test transfer hook,
+/// and its validation state account listing the extra account.
+///
+/// Returns address of the mint account.
+pub fn add_mint_account_with_transfer_hook(program_test: &mut ProgramTest, decimals: u8) -> Pubkey {
+    let address = Pubkey::new_unique();
+
+    let space =
+        ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferHook]).unwrap();
+    let mut data = vec![0u8; space];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    let extension = state.init_extension::<TransferHook>(true).unwrap();
+    extension.program_id = OptionalNonZeroPubkey::try_from(Some(TRANSFER_HOOK_PROGRAM_ID)).unwrap();
+    state.base = Mint {
+        mint_authority: COption::None,
+        supply: u64::MAX,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: spl_token_2022::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        },
+    );
+
+    let extra_account_metas =
+        [ExtraAccountMeta::new_with_pubkey(&TRANSFER_HOOK_EXTRA_ACCOUNT, false, false).unwrap()];
+    let mut data = vec![0u8; ExtraAccountMetaList::size_of(extra_account_metas.len()).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &extra_account_metas).unwrap();
+
+    program_test.add_account(
+        get_extra_account_metas_address(&address, &TRANSFER_HOOK_PROGRAM_ID),
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: TRANSFER_HOOK_PROGRAM_ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        },
+    );
+
+    address
+}
+
+/// Load a Token-2022 token account with the TransferHookAccount extension.
+pub fn add_token_account_with_transfer_hook_account(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    mint_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+    balance: u64,
+) {
+    let space = ExtensionType::try_calculate_account_len::<TokenAccount>(&[
+        ExtensionType::TransferHookAccount,
+    ])
+    .unwrap();
+    let mut data = vec![0u8; space];
+    let mut state =
+        StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+    state.init_extension::<TransferHookAccount>(true).unwrap();
+    state.base = TokenAccount {
+        mint: mint_pubkey,
+        owner: owner_pubkey,
+        amount: balance,
+        delegate: COption::None,
+        state: TokenAccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: spl_token_2022::ID,
+            executable: false,
+            rent_epoch: u64::MAX,
+        },
+    );
+}